@@ -42,6 +42,14 @@ impl SimpleButton {
         }
     }
 
+    /// Move or resize the button, e.g. when the window is resized, keeping its press state.
+    pub fn set_bounds(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.x = x;
+        self.y = y;
+        self.width = width;
+        self.height = height;
+    }
+
     pub fn tick_state(&mut self) -> ButtonAction {
         let mut action = ButtonAction::NoAction;
 
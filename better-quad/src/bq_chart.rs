@@ -0,0 +1,137 @@
+//! Minimal charts: axes, bars, lines, and labels, positioned in data coordinates.
+//!
+//! Data coordinates start at (0, 0) in the bottom left of the plot area, increasing right and up to
+//! (`x_max`, `y_max`) in the top right.
+use crate::bq_text::{draw_text, TextAlignment, TextAnchorPoint};
+use crate::mq;
+
+const TICK_LENGTH: f32 = 6.0;
+const LABEL_GAP: f32 = 4.0;
+
+pub struct Chart {
+    /// Screen rectangle of the plot area. Axes are drawn along its left and bottom edges, and axis
+    /// labels outside of it.
+    plot: mq::Rect,
+    x_max: f32,
+    y_max: f32,
+}
+
+impl Chart {
+    /// Non-positive `x_max`/`y_max` (e.g. no data yet) are treated as 1, so the chart still draws.
+    pub fn new(plot: mq::Rect, x_max: f32, y_max: f32) -> Self {
+        let positive_or_one = |max: f32| if max > 0.0 { max } else { 1.0 };
+        Self {
+            plot,
+            x_max: positive_or_one(x_max),
+            y_max: positive_or_one(y_max),
+        }
+    }
+
+    pub fn plot_rect(&self) -> mq::Rect {
+        self.plot
+    }
+
+    /// Screen position of a data point.
+    pub fn to_screen(&self, x: f32, y: f32) -> mq::Vec2 {
+        mq::vec2(
+            self.plot.x + self.plot.w * (x / self.x_max),
+            self.plot.y + self.plot.h * (1.0 - y / self.y_max),
+        )
+    }
+
+    pub fn draw_axes(&self, thickness: f32, color: mq::Color) {
+        let bottom = self.plot.y + self.plot.h;
+        mq::draw_line(
+            self.plot.x,
+            self.plot.y,
+            self.plot.x,
+            bottom,
+            thickness,
+            color,
+        );
+        mq::draw_line(
+            self.plot.x,
+            bottom,
+            self.plot.x + self.plot.w,
+            bottom,
+            thickness,
+            color,
+        );
+    }
+
+    /// Tick on the x axis at data `x`, labeled underneath.
+    pub fn draw_x_label(&self, x: f32, text: impl AsRef<str>, font_size: u16, color: mq::Color) {
+        let tick = self.to_screen(x, 0.0);
+        mq::draw_line(tick.x, tick.y, tick.x, tick.y + TICK_LENGTH, 1.0, color);
+        draw_text(
+            text,
+            TextAlignment::Center,
+            None,
+            font_size,
+            color,
+            TextAnchorPoint::Center {
+                x: tick.x,
+                y: tick.y + TICK_LENGTH + LABEL_GAP + font_size as f32 / 2.0,
+            },
+            None,
+        );
+    }
+
+    /// Tick on the y axis at data `y`, labeled to the left.
+    pub fn draw_y_label(&self, y: f32, text: impl AsRef<str>, font_size: u16, color: mq::Color) {
+        let tick = self.to_screen(0.0, y);
+        mq::draw_line(tick.x - TICK_LENGTH, tick.y, tick.x, tick.y, 1.0, color);
+        draw_text(
+            text,
+            TextAlignment::Right,
+            None,
+            font_size,
+            color,
+            TextAnchorPoint::TopRight {
+                x: tick.x - TICK_LENGTH - LABEL_GAP,
+                y: tick.y - font_size as f32 / 2.0,
+            },
+            None,
+        );
+    }
+
+    /// Filled rectangle between two data corners.
+    pub fn draw_bar(&self, x_start: f32, x_end: f32, y_start: f32, y_end: f32, color: mq::Color) {
+        let top_left = self.to_screen(x_start.min(x_end), y_start.max(y_end));
+        let bottom_right = self.to_screen(x_start.max(x_end), y_start.min(y_end));
+        mq::draw_rectangle(
+            top_left.x,
+            top_left.y,
+            bottom_right.x - top_left.x,
+            bottom_right.y - top_left.y,
+            color,
+        );
+    }
+
+    /// Line through data `points`, in order.
+    pub fn draw_line(&self, points: &[(f32, f32)], thickness: f32, color: mq::Color) {
+        for segment in points.windows(2) {
+            let start = self.to_screen(segment[0].0, segment[0].1);
+            let end = self.to_screen(segment[1].0, segment[1].1);
+            mq::draw_line(start.x, start.y, end.x, end.y, thickness, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Chart;
+    use crate::mq;
+
+    #[test]
+    fn test_to_screen() {
+        let chart = Chart::new(mq::Rect::new(100.0, 50.0, 200.0, 100.0), 10.0, 4.0);
+        assert_eq!(mq::vec2(100.0, 150.0), chart.to_screen(0.0, 0.0));
+        assert_eq!(mq::vec2(300.0, 50.0), chart.to_screen(10.0, 4.0));
+        assert_eq!(mq::vec2(150.0, 125.0), chart.to_screen(2.5, 1.0));
+
+        // Empty data still maps to the plot area.
+        let empty_chart = Chart::new(mq::Rect::new(0.0, 0.0, 10.0, 10.0), 0.0, 0.0);
+        assert_eq!(mq::vec2(10.0, 0.0), empty_chart.to_screen(1.0, 1.0));
+    }
+}
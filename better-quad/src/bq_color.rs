@@ -8,3 +8,20 @@ pub fn invert_color(color: mq::Color) -> mq::Color {
         a: color.a,
     }
 }
+
+/// Parse a `#RRGGBB` (or `RRGGBB`) hex string.
+pub fn color_from_hex_str(hex: &str) -> Option<mq::Color> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(digits, 16)
+        .ok()
+        .map(mq::Color::from_hex)
+}
+
+/// Format as `#RRGGBB`, ignoring alpha.
+pub fn color_to_hex_str(color: mq::Color) -> String {
+    let [r, g, b, _]: [u8; 4] = color.into();
+    format!("#{r:02X}{g:02X}{b:02X}")
+}
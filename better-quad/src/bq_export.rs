@@ -0,0 +1,46 @@
+//! Hand a generated file (report, record, etc.) to the user.
+//!
+//! On native, the file is written to the working directory. On WASM, the browser downloads it,
+//! which requires `docs/bq_plugin.js`.
+
+/// Returns a short description of where the file went, to show the user.
+pub fn export_file(file_name: &str, contents: &str) -> Result<String, String> {
+    platform::export_file(file_name, contents)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    pub(super) fn export_file(file_name: &str, contents: &str) -> Result<String, String> {
+        std::fs::write(file_name, contents).map_err(|e| format!("{file_name}: {e}"))?;
+        let path = std::fs::canonicalize(file_name)
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| file_name.to_string());
+        Ok(format!("Wrote {path}"))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    // Implemented in docs/bq_plugin.js
+    extern "C" {
+        fn bq_export_download(
+            name_ptr: *const u8,
+            name_len: u32,
+            contents_ptr: *const u8,
+            contents_len: u32,
+        );
+    }
+
+    pub(super) fn export_file(file_name: &str, contents: &str) -> Result<String, String> {
+        // SAFETY: JS only reads the provided ranges.
+        unsafe {
+            bq_export_download(
+                file_name.as_ptr(),
+                file_name.len() as u32,
+                contents.as_ptr(),
+                contents.len() as u32,
+            );
+        }
+        Ok(format!("Downloaded {file_name}"))
+    }
+}
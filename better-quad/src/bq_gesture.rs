@@ -0,0 +1,148 @@
+//! Taps and swipes from touch events, or from mouse drags on devices without touch.
+use crate::mq;
+
+/// Direction a swipe moved in, e.g. `Left` for a finger moving from right to left.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Gesture {
+    /// Released about where it was pressed.
+    Tap(mq::Vec2),
+    Swipe(SwipeDirection),
+}
+
+/// Follows one touch (the first finger down) at a time, so multi-finger touches don't make extra
+/// gestures.
+pub struct GestureDetector {
+    min_swipe_distance: f32,
+    opt_pointer: Option<Pointer>,
+    // Touches also raise mouse events (see `mq::simulate_mouse_with_touch`), which would make each
+    // gesture twice. Once a touch is seen, mouse events are ignored.
+    touch_seen: bool,
+}
+
+#[derive(Copy, Clone)]
+enum Pointer {
+    Touch { id: u64, start: mq::Vec2 },
+    Mouse { start: mq::Vec2 },
+}
+
+impl GestureDetector {
+    /// Releases closer than `min_swipe_distance` pixels to where they were pressed are taps.
+    pub fn new(min_swipe_distance: f32) -> Self {
+        Self {
+            min_swipe_distance,
+            opt_pointer: None,
+            touch_seen: false,
+        }
+    }
+
+    /// Call every frame. Returns the gesture that ended this frame, if any.
+    pub fn tick(&mut self) -> Option<Gesture> {
+        let touches = mq::touches();
+        self.touch_seen |= !touches.is_empty();
+
+        match self.opt_pointer {
+            None => {
+                self.opt_pointer = if let Some(touch) = touches
+                    .iter()
+                    .find(|touch| touch.phase == mq::TouchPhase::Started)
+                {
+                    Some(Pointer::Touch {
+                        id: touch.id,
+                        start: touch.position,
+                    })
+                } else if !self.touch_seen && mq::is_mouse_button_pressed(mq::MouseButton::Left) {
+                    Some(Pointer::Mouse {
+                        start: mq::mouse_position().into(),
+                    })
+                } else {
+                    None
+                };
+                None
+            }
+            Some(Pointer::Touch { id, start }) => {
+                let Some(touch) = touches.iter().find(|touch| touch.id == id) else {
+                    // Lost track of it, e.g. the window lost focus.
+                    self.opt_pointer = None;
+                    return None;
+                };
+                match touch.phase {
+                    mq::TouchPhase::Ended => {
+                        self.opt_pointer = None;
+                        Some(self.gesture(start, touch.position))
+                    }
+                    mq::TouchPhase::Cancelled => {
+                        self.opt_pointer = None;
+                        None
+                    }
+                    _ => None,
+                }
+            }
+            Some(Pointer::Mouse { start }) => {
+                if !mq::is_mouse_button_released(mq::MouseButton::Left) {
+                    return None;
+                }
+                self.opt_pointer = None;
+                Some(self.gesture(start, mq::mouse_position().into()))
+            }
+        }
+    }
+
+    fn gesture(&self, start: mq::Vec2, end: mq::Vec2) -> Gesture {
+        let delta = end - start;
+        if delta.length() < self.min_swipe_distance {
+            return Gesture::Tap(start);
+        }
+        Gesture::Swipe(if delta.x.abs() >= delta.y.abs() {
+            if delta.x < 0.0 {
+                SwipeDirection::Left
+            } else {
+                SwipeDirection::Right
+            }
+        } else if delta.y < 0.0 {
+            SwipeDirection::Up
+        } else {
+            SwipeDirection::Down
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gesture, GestureDetector, SwipeDirection};
+    use crate::mq;
+
+    #[test]
+    fn test_gesture() {
+        let detector = GestureDetector::new(50.0);
+        let start = mq::vec2(100.0, 100.0);
+
+        assert_eq!(
+            Gesture::Tap(start),
+            detector.gesture(start, mq::vec2(120.0, 130.0))
+        );
+        assert_eq!(
+            Gesture::Swipe(SwipeDirection::Left),
+            detector.gesture(start, mq::vec2(20.0, 130.0))
+        );
+        assert_eq!(
+            Gesture::Swipe(SwipeDirection::Right),
+            detector.gesture(start, mq::vec2(200.0, 90.0))
+        );
+        assert_eq!(
+            Gesture::Swipe(SwipeDirection::Up),
+            detector.gesture(start, mq::vec2(90.0, 0.0))
+        );
+        assert_eq!(
+            Gesture::Swipe(SwipeDirection::Down),
+            detector.gesture(start, mq::vec2(100.0, 151.0))
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Small key-value string storage that survives restarts.
+//!
+//! On native, each key is a file in `~/.gui-playground/`. On WASM, keys are stored in the browser's
+//! `localStorage`, which requires `docs/bq_plugin.js` to be loaded before the wasm.
+
+/// Returns `None` if nothing was ever stored for this key (or it can't be read).
+pub fn storage_load(key: &str) -> Option<String> {
+    platform::load(key)
+}
+
+pub fn storage_store(key: &str, value: &str) -> Result<(), String> {
+    platform::store(key, value)
+}
+
+pub fn storage_remove(key: &str) {
+    platform::remove(key)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::path::PathBuf;
+
+    const STORAGE_DIR: &str = ".gui-playground";
+
+    fn key_path(key: &str) -> PathBuf {
+        let base_dir = std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        base_dir.join(STORAGE_DIR).join(key)
+    }
+
+    pub(super) fn load(key: &str) -> Option<String> {
+        std::fs::read_to_string(key_path(key)).ok()
+    }
+
+    pub(super) fn store(key: &str, value: &str) -> Result<(), String> {
+        let path = key_path(key);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        }
+        std::fs::write(&path, value).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub(super) fn remove(key: &str) {
+        let _ = std::fs::remove_file(key_path(key));
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    // Implemented in docs/bq_plugin.js
+    extern "C" {
+        fn bq_storage_get_len(key_ptr: *const u8, key_len: u32) -> i32;
+        fn bq_storage_get(key_ptr: *const u8, key_len: u32, buf_ptr: *mut u8);
+        fn bq_storage_set(key_ptr: *const u8, key_len: u32, value_ptr: *const u8, value_len: u32);
+        fn bq_storage_remove(key_ptr: *const u8, key_len: u32);
+    }
+
+    pub(super) fn load(key: &str) -> Option<String> {
+        // SAFETY: JS only reads `key` and writes exactly `len` bytes into the buffer.
+        unsafe {
+            let len = bq_storage_get_len(key.as_ptr(), key.len() as u32);
+            if len < 0 {
+                return None;
+            }
+            let mut buf = vec![0u8; len as usize];
+            bq_storage_get(key.as_ptr(), key.len() as u32, buf.as_mut_ptr());
+            String::from_utf8(buf).ok()
+        }
+    }
+
+    pub(super) fn store(key: &str, value: &str) -> Result<(), String> {
+        // SAFETY: JS only reads the provided ranges.
+        unsafe {
+            bq_storage_set(
+                key.as_ptr(),
+                key.len() as u32,
+                value.as_ptr(),
+                value.len() as u32,
+            );
+        }
+        Ok(())
+    }
+
+    pub(super) fn remove(key: &str) {
+        // SAFETY: JS only reads the provided range.
+        unsafe { bq_storage_remove(key.as_ptr(), key.len() as u32) }
+    }
+
+    /// Checked by the miniquad JS loader against the plugin's version.
+    #[no_mangle]
+    extern "C" fn better_quad_crate_version() -> u32 {
+        1
+    }
+}
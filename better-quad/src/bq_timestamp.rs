@@ -1,5 +1,5 @@
 use crate::mq;
-use std::ops::Sub;
+use std::ops::{Add, Sub};
 use std::time::Duration;
 
 /// Wrapper of time so we can have a non-primitive type for time to disambiguate UOM.
@@ -30,9 +30,39 @@ impl Timestamp {
         }
     }
 
+    /// Calendar date in UTC as `YYYY-MM-DD`, e.g. to label saved files or records.
+    pub fn utc_date_str(&self) -> String {
+        // Days since 1970-01-01 to a civil date, from Howard Hinnant's `civil_from_days`.
+        let days = (self.seconds / 86_400.0).floor() as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+
     // note: Shouldn't need `elapsed` as their should always be the provided `now` to do math with.
 }
 
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        Self {
+            seconds: self.seconds + rhs.as_secs_f64(),
+        }
+    }
+}
+
 impl Sub for Timestamp {
     type Output = Duration;
 
@@ -40,3 +70,17 @@ impl Sub for Timestamp {
         self.duration_since(rhs).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Timestamp;
+
+    #[test]
+    fn test_utc_date_str() {
+        let date = |seconds| Timestamp { seconds }.utc_date_str();
+        assert_eq!("1970-01-01", date(0.0));
+        assert_eq!("2000-02-29", date(951_782_400.0));
+        assert_eq!("2026-10-18", date(1_792_367_999.5));
+        assert_eq!("1969-12-31", date(-1.0));
+    }
+}
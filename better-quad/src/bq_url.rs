@@ -0,0 +1,105 @@
+//! Query string of the page hosting the WASM build, as a stand-in for command line arguments.
+
+/// Decoded `key=value` pairs of the page URL's query string, in order. Always empty on native.
+///
+/// Requires `docs/bq_plugin.js` on WASM.
+pub fn url_query_params() -> Vec<(String, String)> {
+    parse_query_string(&platform::url_query_string())
+}
+
+/// Parse a query string like `?a=1&b=two%20words`, with or without the leading `?`.
+pub fn parse_query_string(query: &str) -> Vec<(String, String)> {
+    query
+        .strip_prefix('?')
+        .unwrap_or(query)
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(encoded: &str) -> String {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let opt_escaped_byte = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], opt_escaped_byte) {
+            (_, Some(escaped_byte)) => {
+                decoded.push(escaped_byte);
+                i += 3;
+            }
+            (b'+', None) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, None) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    pub(super) fn url_query_string() -> String {
+        String::new()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    // Implemented in docs/bq_plugin.js
+    extern "C" {
+        fn bq_url_query_len() -> u32;
+        fn bq_url_query(buf_ptr: *mut u8);
+    }
+
+    pub(super) fn url_query_string() -> String {
+        // SAFETY: JS writes exactly `len` bytes into the buffer.
+        unsafe {
+            let mut buf = vec![0u8; bq_url_query_len() as usize];
+            bq_url_query(buf.as_mut_ptr());
+            String::from_utf8(buf).unwrap_or_default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_query_string;
+
+    #[test]
+    fn test_parse_query_string() {
+        let to_owned = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(to_owned(&[]), parse_query_string(""));
+        assert_eq!(to_owned(&[]), parse_query_string("?"));
+        assert_eq!(
+            to_owned(&[
+                ("player", "Leo:#00ff00"),
+                ("player", "Dr Who"),
+                ("flag", "")
+            ]),
+            parse_query_string("?player=Leo:%2300ff00&player=Dr+Who&flag")
+        );
+        assert_eq!(
+            to_owned(&[("a", "100%"), ("b", "%zz"), ("c", "é")]),
+            parse_query_string("a=100%&b=%zz&c=%C3%A9")
+        );
+    }
+}
@@ -1,4 +1,4 @@
-/// Infinite repeating iterator around a Vec<T>. Immutable.
+/// Infinite repeating iterator around a Vec<T>. The items themselves can't be added or removed.
 ///
 /// # Panic
 ///
@@ -29,10 +29,33 @@ impl<T> InfiniteIterator<T> {
         &mut self.items[self.current_index]
     }
 
+    /// Any item, not just the current one.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> &mut T {
+        &mut self.items[index]
+    }
+
     pub fn advance(&mut self) {
         self.current_index = (self.current_index + 1) % self.items.len();
     }
 
+    /// Jump directly to an item.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_current_index(&mut self, index: usize) {
+        assert!(
+            index < self.items.len(),
+            "InfiniteIterator index {index} out of bounds (len {})",
+            self.items.len()
+        );
+        self.current_index = index;
+    }
+
     pub fn raw(&self) -> (&Vec<T>, usize) {
         (&self.items, self.current_index)
     }
@@ -49,3 +72,24 @@ impl<T: Clone> From<&[T]> for InfiniteIterator<T> {
         Self::from_vec(value.to_vec())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::InfiniteIterator;
+
+    #[test]
+    fn test_set_current_index_then_advance() {
+        let mut iter = InfiniteIterator::from(vec!['a', 'b', 'c']);
+        iter.set_current_index(1);
+        assert_eq!('b', *iter.current());
+        iter.set_current_index(2);
+        iter.advance();
+        assert_eq!('a', *iter.current());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_current_index_out_of_bounds() {
+        InfiniteIterator::from(vec!['a']).set_current_index(1);
+    }
+}
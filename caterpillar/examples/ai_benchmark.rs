@@ -0,0 +1,34 @@
+//! Plays the computer snake headless over many seeds, to see how well it does and how fast the game
+//! logic runs:
+//!
+//! ```text
+//! cargo run --release -p caterpillar --example ai_benchmark -- [num_seeds] [--versus]
+//! ```
+use caterpillar::SnakeRules;
+
+const DEFAULT_NUM_SEEDS: u64 = 100;
+
+fn main() {
+    let mut num_seeds = DEFAULT_NUM_SEEDS;
+    let mut rules = SnakeRules::default();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--versus" => rules = rules.with_num_players(2),
+            _ => match arg.parse() {
+                Ok(n) => num_seeds = n,
+                Err(_) => {
+                    eprintln!("unknown argument '{arg}' (expected a number of seeds, or --versus)");
+                    std::process::exit(1);
+                }
+            },
+        }
+    }
+
+    match caterpillar::benchmark(&rules, 0..num_seeds) {
+        Ok(results) => println!("{results}"),
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,320 @@
+//! The computer player: head for the nearest fruit by the shortest path, but only if the snake
+//! could still reach its own tail once it's eaten it, so it doesn't coil itself into a dead end.
+//! Otherwise it chases its tail until the way to the fruit is safe, and as a last resort heads for
+//! the most room.
+//!
+//! It only reads the `Game`, so it also plays headless, see `benchmark`.
+use crate::controller::Controller;
+use crate::game::{Game, Snake};
+use crate::rules::SnakeRules;
+use crate::{Direction, Point};
+use better_quad::bq::Timestamp;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::ops::Range;
+use std::time::Duration;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Right,
+    Direction::Left,
+    Direction::Up,
+    Direction::Down,
+];
+
+/// Benchmark games are stopped after this many movement ticks per board cell without eating, since
+/// the AI can end up going round in circles, never finding a safe way to the fruit.
+const MAX_BENCHMARK_TICKS_WITHOUT_EATING_PER_CELL: u64 = 4;
+
+pub(crate) struct PathfindingAi;
+
+impl Controller for PathfindingAi {
+    fn next_turn(&mut self, game: &Game, snake_index: usize) -> Option<Direction> {
+        let snake = &game.snakes[snake_index];
+        // Steer clear of where other snakes' heads could go next, unless there's no other way.
+        let dir = choose_direction(game, snake_index, true)
+            .or_else(|| choose_direction(game, snake_index, false))
+            .unwrap_or(snake.next_dir);
+        (dir != snake.next_dir).then_some(dir)
+    }
+}
+
+fn choose_direction(game: &Game, snake_index: usize, avoid_heads: bool) -> Option<Direction> {
+    let rules = &game.rules;
+    let snake = &game.snakes[snake_index];
+    let others = blocked_by_others(game, snake_index, avoid_heads);
+    // Head first.
+    let cells = snake_cells(snake).collect::<VecDeque<_>>();
+    // The snake can't reverse, even when it's only a head.
+    let behind = rules.resolve_move(snake.head + snake.next_dir.opposite());
+    // The tail moves out of the way.
+    let mut blocked = others.clone();
+    blocked.extend(cells.iter().take(cells.len() - 1));
+
+    let fruits = game
+        .fruits
+        .iter()
+        .map(|fruit| fruit.point)
+        .collect::<HashSet<_>>();
+    if let Some(path) = shortest_path(rules, &blocked, snake.head, behind, |p| fruits.contains(&p))
+    {
+        if is_safe_to_eat(rules, &others, &cells, &path) {
+            return dir_towards(rules, snake.head, path[0]);
+        }
+    }
+
+    // Otherwise take the long way round to the tail, to make room.
+    let moves = DIRECTIONS.into_iter().filter_map(|dir| {
+        let next = rules
+            .resolve_move(snake.head + dir)
+            .filter(|next| Some(*next) != behind && !blocked.contains(next))?;
+        Some((dir, next))
+    });
+    let opt_longest_to_tail = moves
+        .clone()
+        .filter_map(|(dir, next)| {
+            let moved = moved_snake(&cells, &[next], fruits.contains(&next));
+            Some((dir, tail_distance(rules, &others, moved)?))
+        })
+        .max_by_key(|(_, distance)| *distance);
+    if let Some((dir, _)) = opt_longest_to_tail {
+        return Some(dir);
+    }
+
+    moves
+        .max_by_key(|(_, next)| reachable_area(rules, &blocked, *next))
+        .map(|(dir, _)| dir)
+}
+
+/// Head first, then the body.
+fn snake_cells(snake: &Snake) -> impl Iterator<Item = Point> + '_ {
+    std::iter::once(snake.head).chain(snake.body.iter().copied())
+}
+
+/// Obstacles and the other snakes, and with `avoid_heads`, every cell the other snakes' heads
+/// could move into next.
+fn blocked_by_others(game: &Game, snake_index: usize, avoid_heads: bool) -> HashSet<Point> {
+    let mut blocked = game.rules.obstacles.clone();
+    for (i, other) in game.snakes.iter().enumerate() {
+        if i == snake_index {
+            continue;
+        }
+        blocked.extend(snake_cells(other));
+        if avoid_heads && other.alive {
+            blocked.extend(
+                DIRECTIONS
+                    .into_iter()
+                    .filter_map(|dir| game.rules.resolve_move(other.head + dir)),
+            );
+        }
+    }
+    blocked
+}
+
+/// Whether, after following `path` to a fruit and growing, the snake could still reach its tail
+/// (or fills the board).
+fn is_safe_to_eat(
+    rules: &SnakeRules,
+    others: &HashSet<Point>,
+    cells: &VecDeque<Point>,
+    path: &[Point],
+) -> bool {
+    let moved = moved_snake(cells, path, true);
+    if tail_distance(rules, others, moved.clone()).is_some() {
+        return true;
+    }
+    (0..rules.board_height)
+        .flat_map(|y| (0..rules.board_width).map(move |x| (x, y)))
+        .all(|p| others.contains(&p) || moved.contains(&p))
+}
+
+/// The snake's cells, head first, after following `path`, one longer if it `grows`.
+fn moved_snake(cells: &VecDeque<Point>, path: &[Point], grows: bool) -> VecDeque<Point> {
+    path.iter()
+        .rev()
+        .chain(cells)
+        .copied()
+        .take(cells.len() + usize::from(grows))
+        .collect()
+}
+
+/// Length of the shortest path from the snake's head to its tail, if there is one.
+fn tail_distance(
+    rules: &SnakeRules,
+    others: &HashSet<Point>,
+    mut cells: VecDeque<Point>,
+) -> Option<usize> {
+    let tail = cells.pop_back()?;
+    if cells.is_empty() {
+        // Just a head, it's free to go anywhere.
+        return Some(0);
+    }
+    if cells.contains(&tail) {
+        // Still coiled up at the start, so the tail doesn't move out of the way yet.
+        return None;
+    }
+    let mut blocked = others.clone();
+    blocked.extend(&cells);
+    shortest_path(rules, &blocked, cells[0], None, |p| p == tail).map(|path| path.len())
+}
+
+/// Shortest path from `from` to the nearest cell that `is_target`, as the cells after `from`.
+/// Targets may be `blocked`, like a tail that moves out of the way. The first step can't be to
+/// `opt_behind`.
+fn shortest_path(
+    rules: &SnakeRules,
+    blocked: &HashSet<Point>,
+    from: Point,
+    opt_behind: Option<Point>,
+    is_target: impl Fn(Point) -> bool,
+) -> Option<Vec<Point>> {
+    let mut came_from = HashMap::from([(from, from)]);
+    let mut queue = VecDeque::from([from]);
+    while let Some(point) = queue.pop_front() {
+        for dir in DIRECTIONS {
+            let Some(next) = rules.resolve_move(point + dir) else {
+                continue;
+            };
+            if came_from.contains_key(&next) || (point == from && Some(next) == opt_behind) {
+                continue;
+            }
+            if is_target(next) {
+                let mut path = vec![next];
+                let mut step = point;
+                while step != from {
+                    path.push(step);
+                    step = came_from[&step];
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if blocked.contains(&next) {
+                continue;
+            }
+            came_from.insert(next, point);
+            queue.push_back(next);
+        }
+    }
+    None
+}
+
+/// Free cells reachable from `from`, including itself.
+fn reachable_area(rules: &SnakeRules, blocked: &HashSet<Point>, from: Point) -> usize {
+    let mut seen = HashSet::from([from]);
+    let mut queue = VecDeque::from([from]);
+    while let Some(point) = queue.pop_front() {
+        for dir in DIRECTIONS {
+            if let Some(next) = rules.resolve_move(point + dir) {
+                if !blocked.contains(&next) && seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    seen.len()
+}
+
+fn dir_towards(rules: &SnakeRules, from: Point, to: Point) -> Option<Direction> {
+    DIRECTIONS
+        .into_iter()
+        .find(|dir| rules.resolve_move(from + *dir) == Some(to))
+}
+
+/// How the computer did over a set of seeds, from `benchmark`.
+pub struct AiBenchmark {
+    pub num_games: u64,
+    /// Games that ended by filling the board.
+    pub num_won: u64,
+    /// Games stopped for going too long without eating.
+    pub num_stalled: u64,
+    /// Of every snake in every game.
+    pub total_score: u64,
+    pub best_score: u64,
+    pub total_ticks: u64,
+    /// Real time taken to play them all.
+    pub duration: Duration,
+}
+
+/// Play a game for each of `seeds`, with the computer steering every snake, as fast as it goes.
+pub fn benchmark(rules: &SnakeRules, seeds: Range<u64>) -> Result<AiBenchmark, String> {
+    rules.validate()?;
+    let num_cells = rules.board_width as u64 * rules.board_height as u64;
+    let max_ticks_without_eating = MAX_BENCHMARK_TICKS_WITHOUT_EATING_PER_CELL * num_cells;
+    let started = Timestamp::now();
+    let mut results = AiBenchmark {
+        num_games: 0,
+        num_won: 0,
+        num_stalled: 0,
+        total_score: 0,
+        best_score: 0,
+        total_ticks: 0,
+        duration: Duration::ZERO,
+    };
+    let mut ai = PathfindingAi;
+    for seed in seeds {
+        let mut game = Game::new(rules.clone(), seed);
+        let total_score = |game: &Game| game.snakes.iter().map(|snake| snake.score).sum::<u64>();
+        let mut last_ate = (0, total_score(&game));
+        while !game.game_over && game.num_ticks - last_ate.0 < max_ticks_without_eating {
+            let turns = (0..game.snakes.len())
+                .map(|snake_index| ai.next_turn(&game, snake_index))
+                .collect::<Vec<_>>();
+            game.tick(&turns);
+            if total_score(&game) != last_ate.1 {
+                last_ate = (game.num_ticks, total_score(&game));
+            }
+        }
+
+        results.num_games += 1;
+        results.num_won += u64::from(game.won);
+        results.num_stalled += u64::from(!game.game_over);
+        for snake in &game.snakes {
+            results.total_score += snake.score;
+            results.best_score = results.best_score.max(snake.score);
+        }
+        results.total_ticks += game.num_ticks;
+    }
+    results.duration = Timestamp::now() - started;
+    Ok(results)
+}
+
+impl fmt::Display for AiBenchmark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let num_games = self.num_games.max(1) as f64;
+        writeln!(
+            f,
+            "{} games: {} won, {} stopped, average score {:.0}, best {}",
+            self.num_games,
+            self.num_won,
+            self.num_stalled,
+            self.total_score as f64 / num_games,
+            self.best_score,
+        )?;
+        write!(
+            f,
+            "{} movement ticks in {:.2?} ({:.0} ticks/s)",
+            self.total_ticks,
+            self.duration,
+            self.total_ticks as f64 / self.duration.as_secs_f64().max(f64::EPSILON),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::benchmark;
+    use crate::SnakeRules;
+
+    #[test]
+    fn test_benchmark() {
+        let rules = SnakeRules::from_level(&"........\n".repeat(8)).unwrap();
+        let results = benchmark(&rules, 0..5).unwrap();
+        assert_eq!(5, results.num_games);
+        // It never traps itself, it only ever wins or runs out of safe ways to the fruit.
+        assert_eq!(results.num_games, results.num_won + results.num_stalled);
+        assert!(results.best_score >= 2000, "{results}");
+
+        let versus = benchmark(&rules.with_num_players(2), 0..5).unwrap();
+        assert_eq!(5, versus.num_games);
+        assert!(versus.total_ticks > 0);
+    }
+}
@@ -0,0 +1,13 @@
+//! What steers each snake: a player on the keyboard (`InputQueue`), or the computer
+//! (`PathfindingAi`).
+use crate::game::Game;
+use crate::Direction;
+
+pub(crate) trait Controller {
+    /// Called every frame while playing, between movement ticks, e.g. to read keys.
+    fn update(&mut self, _game: &Game, _snake_index: usize) {}
+
+    /// Direction to turn the snake at `snake_index` to on this movement tick, if any. Like with
+    /// `Game::tick`, it mustn't be a reversal.
+    fn next_turn(&mut self, game: &Game, snake_index: usize) -> Option<Direction>;
+}
@@ -0,0 +1,121 @@
+//! Fruit to eat: where it spawns, and the special kinds that come and go.
+use crate::rules::SnakeRules;
+use crate::Point;
+use better_quad::mq;
+use std::collections::HashSet;
+
+/// Chance that eating a fruit also spawns a special fruit, if there isn't one already.
+const SPECIAL_FRUIT_CHANCE: f32 = 0.25;
+/// Random board cells tried before picking from the list of free cells.
+const RANDOM_CELL_ATTEMPTS: usize = 32;
+/// Special fruits disappear after this many movement ticks if they aren't eaten.
+pub(crate) const SPECIAL_FRUIT_TICKS: u32 = 40;
+/// Movement ticks of ghost mode from eating a ghost fruit.
+pub(crate) const GHOST_TICKS: u32 = 30;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum FruitKind {
+    Regular,
+    /// Extra points.
+    Bonus,
+    /// Undoes some of the speed-ups.
+    SpeedDown,
+    /// Pass through your own body and obstacles for a while.
+    Ghost,
+}
+
+const SPECIAL_FRUIT_KINDS: [FruitKind; 3] =
+    [FruitKind::Bonus, FruitKind::SpeedDown, FruitKind::Ghost];
+
+impl FruitKind {
+    pub(crate) fn points(self) -> u64 {
+        match self {
+            Self::Regular | Self::SpeedDown | Self::Ghost => 100,
+            Self::Bonus => 500,
+        }
+    }
+
+    pub(crate) fn color(self) -> mq::Color {
+        match self {
+            Self::Regular => mq::GOLD,
+            Self::Bonus => mq::MAGENTA,
+            Self::SpeedDown => mq::SKYBLUE,
+            Self::Ghost => mq::Color::new(0.6, 0.6, 0.6, 0.6),
+        }
+    }
+}
+
+pub(crate) struct Fruit {
+    pub(crate) point: Point,
+    pub(crate) kind: FruitKind,
+    /// Movement ticks until it disappears, or `None` to stay until eaten.
+    pub(crate) opt_ticks_left: Option<u32>,
+}
+
+impl Fruit {
+    pub(crate) fn regular(point: Point) -> Self {
+        Self {
+            point,
+            kind: FruitKind::Regular,
+            opt_ticks_left: None,
+        }
+    }
+
+    /// A random special kind, or `None` most of the time.
+    pub(crate) fn maybe_special(point: Point, rng: &mq::rand::RandGenerator) -> Option<Self> {
+        if rng.gen_range(0.0, 1.0) >= SPECIAL_FRUIT_CHANCE {
+            return None;
+        }
+        let kind = SPECIAL_FRUIT_KINDS[rng.gen_range(0, SPECIAL_FRUIT_KINDS.len())];
+        Some(Self {
+            point,
+            kind,
+            opt_ticks_left: Some(SPECIAL_FRUIT_TICKS),
+        })
+    }
+
+    /// Count down a movement tick. Returns whether it's still there.
+    pub(crate) fn tick(&mut self) -> bool {
+        match &mut self.opt_ticks_left {
+            None => true,
+            Some(ticks_left) => {
+                *ticks_left = ticks_left.saturating_sub(1);
+                *ticks_left > 0
+            }
+        }
+    }
+}
+
+/// Cells on the board that aren't obstacles or `occupied`, in reading order.
+pub(crate) fn free_cells(rules: &SnakeRules, occupied: &HashSet<Point>) -> Vec<Point> {
+    (0..rules.board_height)
+        .flat_map(|y| (0..rules.board_width).map(move |x| (x, y)))
+        .filter(|point| !rules.obstacles.contains(point) && !occupied.contains(point))
+        .collect()
+}
+
+/// Uniformly random cell that isn't an obstacle or `occupied`, or `None` if there aren't any.
+///
+/// Random board cells are tried first, so games with the same seed get the same fruit cells
+/// whenever they're free, however differently the snakes moved. Crowded boards fall back to
+/// picking from the free cells.
+pub(crate) fn random_free_cell(
+    rules: &SnakeRules,
+    occupied: &HashSet<Point>,
+    rng: &mq::rand::RandGenerator,
+) -> Option<Point> {
+    for _ in 0..RANDOM_CELL_ATTEMPTS {
+        let point = (
+            rng.gen_range(0, rules.board_width),
+            rng.gen_range(0, rules.board_height),
+        );
+        if !rules.obstacles.contains(&point) && !occupied.contains(&point) {
+            return Some(point);
+        }
+    }
+    let cells = free_cells(rules, occupied);
+    if cells.is_empty() {
+        return None;
+    }
+    Some(cells[rng.gen_range(0, cells.len())])
+}
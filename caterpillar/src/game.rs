@@ -0,0 +1,362 @@
+//! The game itself, without input, timing, or drawing: a seed and the turns taken on each movement
+//! tick always play out the same, so runs can be recorded and replayed exactly.
+use crate::fruit::{self, Fruit, FruitKind};
+use crate::rules::SnakeRules;
+use crate::{Direction, Point};
+use better_quad::mq;
+use std::collections::{HashSet, LinkedList};
+use std::time::Duration;
+
+/// Tick movement every period
+pub(crate) const INITIAL_MOVEMENT_TICK_SPEED: Duration = Duration::from_millis(200);
+/// Gradually move faster.
+/// Current config = `[0.2, 0.18, 0.162, 0.1458, ...]`
+const MOVEMENT_TICK_SPEED_MULTIPLICATIVE_FACTOR: f64 = 0.9;
+/// How many speed-ups a speed down fruit undoes.
+const SPEED_DOWN_STEPS: i32 = 2;
+/// Special fruits draw from their own generator, so whether they show up doesn't shift where
+/// regular fruits spawn.
+const SPECIAL_FRUIT_SEED_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+
+pub(crate) struct Game {
+    pub(crate) rules: SnakeRules,
+    pub(crate) seed: u64,
+    fruit_rng: mq::rand::RandGenerator,
+    special_fruit_rng: mq::rand::RandGenerator,
+    /// One per player, in player order.
+    pub(crate) snakes: Vec<Snake>,
+    pub(crate) fruits: Vec<Fruit>,
+    /// Shared by all the snakes, sped up by any of them eating.
+    pub(crate) movement_tick_speed: Duration,
+    /// Movement ticks played so far.
+    pub(crate) num_ticks: u64,
+    /// Play time, as the sum of the movement tick periods so far. Unlike wall time, it's the same
+    /// when the run is replayed.
+    pub(crate) elapsed: Duration,
+    /// Shortest movement tick period reached.
+    pub(crate) fastest_tick_speed: Duration,
+    /// Once any snake crashes, or the board is full.
+    pub(crate) game_over: bool,
+    /// The game ended by filling the whole board.
+    pub(crate) won: bool,
+}
+
+pub(crate) struct Snake {
+    pub(crate) head: Point,
+    pub(crate) body: LinkedList<Point>,
+    pub(crate) next_dir: Direction,
+    /// Where the tail was before the last move, to draw it sliding along.
+    pub(crate) prev_tail: Point,
+    /// Movement ticks left of passing through bodies and obstacles.
+    pub(crate) ghost_ticks_left: u32,
+    pub(crate) score: u64,
+    /// Until it crashes. Crashed snakes stay where they are.
+    pub(crate) alive: bool,
+}
+
+impl Snake {
+    /// Including the head.
+    pub(crate) fn length(&self) -> usize {
+        self.body.len() + 1
+    }
+}
+
+impl Game {
+    /// `rules` must already be validated.
+    pub(crate) fn new(rules: SnakeRules, seed: u64) -> Self {
+        let fruit_rng = mq::rand::RandGenerator::new();
+        fruit_rng.srand(seed);
+        let special_fruit_rng = mq::rand::RandGenerator::new();
+        special_fruit_rng.srand(seed ^ SPECIAL_FRUIT_SEED_SALT);
+        let snakes = rules
+            .starts()
+            .into_iter()
+            .map(|(start, dir)| Snake {
+                head: start,
+                body: std::iter::repeat_n(start, rules.initial_length - 1).collect(),
+                next_dir: dir,
+                prev_tail: start,
+                ghost_ticks_left: 0,
+                score: 0,
+                alive: true,
+            })
+            .collect();
+        let mut game = Self {
+            rules,
+            seed,
+            fruit_rng,
+            special_fruit_rng,
+            snakes,
+            fruits: Vec::new(),
+            movement_tick_speed: INITIAL_MOVEMENT_TICK_SPEED,
+            num_ticks: 0,
+            elapsed: Duration::ZERO,
+            fastest_tick_speed: INITIAL_MOVEMENT_TICK_SPEED,
+            game_over: false,
+            won: false,
+        };
+        game.spawn_fruits(false);
+        game
+    }
+
+    /// Fastest speed reached, in moves per second.
+    pub(crate) fn max_speed(&self) -> f64 {
+        1.0 / self.fastest_tick_speed.as_secs_f64()
+    }
+
+    /// The snake that won a finished versus round, or `None` for a draw. The last snake standing
+    /// wins, or the most points if they filled the board together.
+    pub(crate) fn round_winner(&self) -> Option<usize> {
+        let alive = (0..self.snakes.len())
+            .filter(|i| self.snakes[*i].alive)
+            .collect::<Vec<_>>();
+        match alive.as_slice() {
+            [] => None,
+            [winner] => Some(*winner),
+            _ => {
+                let best_score = alive.iter().map(|i| self.snakes[*i].score).max()?;
+                let mut best = alive
+                    .into_iter()
+                    .filter(|i| self.snakes[*i].score == best_score);
+                let winner = best.next();
+                // Tied on points.
+                best.next().is_none().then_some(winner).flatten()
+            }
+        }
+    }
+
+    fn snake_cells(&self) -> HashSet<Point> {
+        self.snakes
+            .iter()
+            .flat_map(|snake| std::iter::once(snake.head).chain(snake.body.iter().copied()))
+            .collect()
+    }
+
+    /// Move every snake a cell at once, each turning to its entry of `turns` first, and deal with
+    /// whatever they run into. Turns must be valid compared to the snake's current direction (see
+    /// `InputQueue`), and missing entries don't turn.
+    pub(crate) fn tick(&mut self, turns: &[Option<Direction>]) {
+        if self.game_over {
+            return;
+        }
+        self.num_ticks += 1;
+        self.elapsed += self.movement_tick_speed;
+
+        let old_heads = self
+            .snakes
+            .iter()
+            .map(|snake| snake.head)
+            .collect::<Vec<_>>();
+        let mut new_heads = Vec::with_capacity(self.snakes.len());
+        for (i, snake) in self.snakes.iter_mut().enumerate() {
+            if let Some(dir) = turns.get(i).copied().flatten() {
+                snake.next_dir = dir;
+            }
+            let opt_new_head = self.rules.resolve_move(snake.head + snake.next_dir);
+            if opt_new_head.is_none() {
+                // Hit a wall
+                snake.alive = false;
+            }
+            new_heads.push(opt_new_head);
+        }
+
+        // Head-to-head: moving into the same cell, or through each other.
+        for i in 0..self.snakes.len() {
+            for j in (i + 1)..self.snakes.len() {
+                let (Some(head_i), Some(head_j)) = (new_heads[i], new_heads[j]) else {
+                    continue;
+                };
+                if head_i == head_j || (head_i == old_heads[j] && head_j == old_heads[i]) {
+                    self.snakes[i].alive = false;
+                    self.snakes[j].alive = false;
+                }
+            }
+        }
+
+        let mut ate_fruit = false;
+        let mut ghosts = Vec::with_capacity(self.snakes.len());
+        for (i, opt_new_head) in new_heads.into_iter().enumerate() {
+            let snake = &mut self.snakes[i];
+            ghosts.push(snake.ghost_ticks_left > 0);
+            let Some(new_head) = opt_new_head.filter(|_| snake.alive) else {
+                continue;
+            };
+            snake.body.push_front(snake.head);
+            snake.head = new_head;
+            snake.ghost_ticks_left = snake.ghost_ticks_left.saturating_sub(1);
+
+            let opt_eaten_index = self.fruits.iter().position(|fruit| fruit.point == new_head);
+            if let Some(fruit_index) = opt_eaten_index {
+                // Grow!
+                let fruit = self.fruits.swap_remove(fruit_index);
+                self.eat(i, fruit.kind);
+                self.fastest_tick_speed = self.fastest_tick_speed.min(self.movement_tick_speed);
+                ate_fruit = true;
+                // The tail stays put while growing.
+                let snake = &mut self.snakes[i];
+                snake.prev_tail = *snake.body.back().expect("Just pushed the old head");
+            } else {
+                // Normal movement.
+                let snake = &mut self.snakes[i];
+                snake.prev_tail = snake.body.pop_back().expect("Just pushed the old head");
+            }
+        }
+        self.fruits.retain_mut(Fruit::tick);
+
+        // Head-to-body, including crashed snakes' heads. Ghosts pass through obstacles and bodies.
+        let crashed = (0..self.snakes.len())
+            .map(|i| {
+                let head = self.snakes[i].head;
+                self.snakes[i].alive
+                    && !ghosts[i]
+                    && (self.rules.obstacles.contains(&head)
+                        || self.snakes.iter().enumerate().any(|(j, other)| {
+                            other.body.contains(&head) || (j != i && other.head == head)
+                        }))
+            })
+            .collect::<Vec<_>>();
+        for (snake, crashed) in self.snakes.iter_mut().zip(crashed) {
+            if crashed {
+                snake.alive = false;
+            }
+        }
+
+        if self.snakes.iter().any(|snake| !snake.alive) {
+            self.game_over = true;
+            return;
+        }
+
+        self.spawn_fruits(ate_fruit);
+    }
+
+    fn eat(&mut self, snake_index: usize, kind: FruitKind) {
+        self.snakes[snake_index].score += kind.points();
+        let speed_secs = self.movement_tick_speed.as_secs_f64();
+        match kind {
+            FruitKind::Regular => {
+                self.movement_tick_speed =
+                    Duration::from_secs_f64(speed_secs * MOVEMENT_TICK_SPEED_MULTIPLICATIVE_FACTOR);
+            }
+            FruitKind::Bonus => {}
+            FruitKind::SpeedDown => {
+                let slowed_down = Duration::from_secs_f64(
+                    speed_secs / MOVEMENT_TICK_SPEED_MULTIPLICATIVE_FACTOR.powi(SPEED_DOWN_STEPS),
+                );
+                self.movement_tick_speed = slowed_down.min(INITIAL_MOVEMENT_TICK_SPEED);
+            }
+            FruitKind::Ghost => self.snakes[snake_index].ghost_ticks_left = fruit::GHOST_TICKS,
+        }
+    }
+
+    /// Top up the regular fruits, and maybe add a special one after eating. Fruit only spawns on
+    /// free cells, and once the snakes fill the whole board, it's won.
+    fn spawn_fruits(&mut self, ate_fruit: bool) {
+        let mut occupied = self.snake_cells();
+        if fruit::free_cells(&self.rules, &occupied).is_empty() {
+            self.game_over = true;
+            self.won = true;
+            return;
+        }
+
+        occupied.extend(self.fruits.iter().map(|fruit| fruit.point));
+        let num_regular = self
+            .fruits
+            .iter()
+            .filter(|fruit| fruit.kind == FruitKind::Regular)
+            .count();
+        for _ in num_regular..self.rules.num_fruits {
+            let Some(point) = fruit::random_free_cell(&self.rules, &occupied, &self.fruit_rng)
+            else {
+                break;
+            };
+            occupied.insert(point);
+            self.fruits.push(Fruit::regular(point));
+        }
+
+        let has_special = self
+            .fruits
+            .iter()
+            .any(|fruit| fruit.kind != FruitKind::Regular);
+        if ate_fruit && !has_special {
+            let rng = &self.special_fruit_rng;
+            if let Some(special) = fruit::random_free_cell(&self.rules, &occupied, rng)
+                .and_then(|point| Fruit::maybe_special(point, rng))
+            {
+                self.fruits.push(special);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Game;
+    use crate::{Direction, SnakeRules};
+
+    #[test]
+    fn test_fruit_fills_board() {
+        let rules = SnakeRules::from_level("S.").unwrap();
+        let mut game = Game::new(rules, 0);
+        // The only free cell, since fruit never spawns on the snake.
+        assert_eq!(
+            vec![(1, 0)],
+            game.fruits.iter().map(|f| f.point).collect::<Vec<_>>()
+        );
+
+        game.tick(&[]);
+        assert!(game.game_over);
+        assert!(game.won);
+        assert_eq!(100, game.snakes[0].score);
+        assert_eq!(2, game.snakes[0].length());
+        assert!(game.max_speed() > 5.0);
+    }
+
+    #[test]
+    fn test_same_seed_same_game() {
+        let play = |seed| {
+            let mut game = Game::new(SnakeRules::default().with_num_fruits(3), seed);
+            for turn in [None, Some(Direction::Down), None, Some(Direction::Right)] {
+                game.tick(&[turn]);
+            }
+            (
+                game.snakes[0].head,
+                game.fruits.iter().map(|f| f.point).collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(play(1234), play(1234));
+        assert_ne!(play(1234).1, play(4321).1);
+    }
+
+    #[test]
+    fn test_versus_collisions() {
+        // Player 1 at `head_1`, and player 2 at `head_2` with `body_2` behind, on an empty board.
+        let versus = |head_1, dir_1, head_2, dir_2, body_2: &[(i16, i16)]| {
+            let rules = SnakeRules::default().with_num_players(2);
+            let mut game = Game::new(rules, 0);
+            game.fruits.clear();
+            (game.snakes[0].head, game.snakes[0].next_dir) = (head_1, dir_1);
+            (game.snakes[1].head, game.snakes[1].next_dir) = (head_2, dir_2);
+            game.snakes[1].body = body_2.iter().copied().collect();
+            game
+        };
+
+        // Head-to-head into the same cell: both crash, a draw.
+        let mut game = versus((1, 0), Direction::Right, (3, 0), Direction::Left, &[]);
+        game.tick(&[]);
+        assert!(game.game_over);
+        assert!(game.snakes.iter().all(|snake| !snake.alive));
+        assert_eq!(None, game.round_winner());
+
+        // Passing through each other counts too.
+        let mut game = versus((1, 0), Direction::Right, (2, 0), Direction::Left, &[]);
+        game.tick(&[]);
+        assert!(game.snakes.iter().all(|snake| !snake.alive));
+
+        // Head-to-body: the snake running into the other's body loses.
+        let body_2 = [(2, 1), (3, 1)];
+        let mut game = versus((2, 0), Direction::Right, (1, 1), Direction::Left, &body_2);
+        game.tick(&[Some(Direction::Down), None]);
+        assert!(game.game_over);
+        assert_eq!(Some(1), game.round_winner());
+    }
+}
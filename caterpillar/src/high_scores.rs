@@ -0,0 +1,160 @@
+//! Best games, kept across restarts.
+use crate::game::Game;
+use better_quad::bq::{self, Timestamp};
+use better_quad::mq;
+use nanoserde::{DeJson, SerJson};
+
+const HIGH_SCORES_KEY: &str = "caterpillar-high-scores";
+const SAVED_PLAYER_NAME_KEY: &str = "caterpillar-player-name";
+
+pub(crate) const MAX_HIGH_SCORES: usize = 10;
+pub(crate) const MAX_PLAYER_NAME_LEN: usize = 16;
+
+// `pub` since nanoserde derives don't support `pub(crate)`. The module is private anyway.
+#[derive(SerJson, DeJson, Clone, Default)]
+pub struct HighScores {
+    /// Best first.
+    pub entries: Vec<HighScore>,
+}
+
+#[derive(SerJson, DeJson, Clone, PartialEq, Debug)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u64,
+    pub length: usize,
+    /// Play time, see `Game::elapsed`.
+    pub duration_ms: u64,
+    /// Fastest speed reached, in moves per second.
+    pub max_speed: f64,
+    /// UTC `YYYY-MM-DD` of when it was played.
+    pub date: String,
+}
+
+impl HighScore {
+    /// The single player's game.
+    pub(crate) fn capture(game: &Game, name: String, now: Timestamp) -> Self {
+        let snake = &game.snakes[0];
+        Self {
+            name,
+            score: snake.score,
+            length: snake.length(),
+            duration_ms: game.elapsed.as_millis() as u64,
+            max_speed: game.max_speed(),
+            date: now.utc_date_str(),
+        }
+    }
+}
+
+impl HighScores {
+    /// Whether `score` would make the table. Scoreless games never do.
+    pub(crate) fn qualifies(&self, score: u64) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.last().is_some_and(|entry| score > entry.score))
+    }
+
+    /// Add `entry` in order, dropping whatever falls off the bottom. Returns its rank (0 is the
+    /// best), or `None` if it didn't make the table. Ties go below older entries.
+    pub(crate) fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|existing| entry.score > existing.score)
+            .unwrap_or(self.entries.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+/// The saved table, or an empty one if there's none yet (or it can't be read).
+pub(crate) fn load() -> HighScores {
+    let Some(json) = bq::storage_load(HIGH_SCORES_KEY) else {
+        return HighScores::default();
+    };
+    HighScores::deserialize_json(&json).unwrap_or_else(|e| {
+        mq::warn!("Ignoring high scores: {e}");
+        HighScores::default()
+    })
+}
+
+pub(crate) fn save(high_scores: &HighScores) -> Result<(), String> {
+    let json = json_to_save(bq::storage_load(HIGH_SCORES_KEY).as_deref(), high_scores)?;
+    bq::storage_store(HIGH_SCORES_KEY, &json)
+}
+
+/// `high_scores` as JSON to save over the table stored as `opt_stored_json`. A stored table that
+/// can't be read is an error, since it was loaded as empty and its scores would be lost.
+fn json_to_save(opt_stored_json: Option<&str>, high_scores: &HighScores) -> Result<String, String> {
+    if let Some(json) = opt_stored_json {
+        HighScores::deserialize_json(json)
+            .map_err(|e| format!("the saved high scores can't be read ({e})"))?;
+    }
+    Ok(high_scores.serialize_json())
+}
+
+/// Name from the last high score entered, if any.
+pub(crate) fn load_saved_player_name() -> String {
+    bq::storage_load(SAVED_PLAYER_NAME_KEY).unwrap_or_default()
+}
+
+pub(crate) fn save_player_name(name: &str) {
+    if let Err(e) = bq::storage_store(SAVED_PLAYER_NAME_KEY, name) {
+        mq::warn!("Failed to save player name: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json_to_save, HighScore, HighScores, MAX_HIGH_SCORES};
+
+    fn entry(name: &str, score: u64) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            length: 1,
+            duration_ms: 0,
+            max_speed: 5.0,
+            date: "2026-01-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut high_scores = HighScores::default();
+        assert!(!high_scores.qualifies(0));
+        assert_eq!(Some(0), high_scores.insert(entry("a", 300)));
+        assert_eq!(Some(1), high_scores.insert(entry("b", 100)));
+        // Ties go below.
+        assert_eq!(Some(1), high_scores.insert(entry("c", 300)));
+        let names = |high_scores: &HighScores| {
+            high_scores
+                .entries
+                .iter()
+                .map(|e| e.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["a", "c", "b"], names(&high_scores));
+
+        for i in 0..MAX_HIGH_SCORES {
+            high_scores.insert(entry("d", 200 + i as u64));
+        }
+        assert_eq!(MAX_HIGH_SCORES, high_scores.entries.len());
+        assert!(!high_scores.qualifies(200));
+        assert_eq!(None, high_scores.insert(entry("e", 200)));
+        assert_eq!(Some(&entry("a", 300)), high_scores.entries.first());
+    }
+
+    #[test]
+    fn test_json_to_save() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(entry("a", 300));
+        let json = json_to_save(None, &high_scores).unwrap();
+        assert!(json_to_save(Some(&json), &high_scores).is_ok());
+        // Not saved over, so the scores in it can still be recovered.
+        assert!(json_to_save(Some("{\"entries\": [{"), &high_scores).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Buffered direction key presses, so quick inputs (like a U-turn) all apply, one per movement
+//! tick.
+use crate::controller::Controller;
+use crate::game::Game;
+use crate::Direction;
+use better_quad::mq;
+use std::collections::VecDeque;
+
+/// A player's turning keys, checked in this order when several are pressed in the same frame.
+pub(crate) type DirectionKeys = [(mq::KeyCode, Direction); 4];
+
+pub(crate) const ARROW_KEYS: DirectionKeys = [
+    (mq::KeyCode::Right, Direction::Right),
+    (mq::KeyCode::Left, Direction::Left),
+    (mq::KeyCode::Up, Direction::Up),
+    (mq::KeyCode::Down, Direction::Down),
+];
+
+pub(crate) const WASD_KEYS: DirectionKeys = [
+    (mq::KeyCode::D, Direction::Right),
+    (mq::KeyCode::A, Direction::Left),
+    (mq::KeyCode::W, Direction::Up),
+    (mq::KeyCode::S, Direction::Down),
+];
+
+pub(crate) struct InputQueue {
+    max_depth: usize,
+    keys: DirectionKeys,
+    queued_dirs: VecDeque<Direction>,
+}
+
+impl InputQueue {
+    /// Presses of `keys` past `max_depth` waiting inputs are dropped.
+    pub(crate) fn new(max_depth: usize, keys: DirectionKeys) -> Self {
+        Self {
+            max_depth,
+            keys,
+            queued_dirs: VecDeque::with_capacity(max_depth),
+        }
+    }
+
+    /// Queue direction keys pressed this frame. Held keys don't repeat.
+    pub(crate) fn push_pressed_keys(&mut self, current_dir: Direction) {
+        for (key, dir) in self.keys {
+            if mq::is_key_pressed(key) {
+                self.push(dir, current_dir);
+            }
+        }
+    }
+
+    /// Queue `dir` unless it's redundant or reversing compared to the direction it'd follow: the
+    /// last queued one, or `current_dir` if none are queued.
+    pub(crate) fn push(&mut self, dir: Direction, current_dir: Direction) {
+        let prev_dir = self.queued_dirs.back().copied().unwrap_or(current_dir);
+        if dir == prev_dir || dir == prev_dir.opposite() {
+            return;
+        }
+        if self.queued_dirs.len() < self.max_depth {
+            self.queued_dirs.push_back(dir);
+        }
+    }
+
+    /// Direction to turn to on this movement tick, if any.
+    pub(crate) fn pop(&mut self) -> Option<Direction> {
+        self.queued_dirs.pop_front()
+    }
+}
+
+impl Controller for InputQueue {
+    fn update(&mut self, game: &Game, snake_index: usize) {
+        self.push_pressed_keys(game.snakes[snake_index].next_dir);
+    }
+
+    fn next_turn(&mut self, _game: &Game, _snake_index: usize) -> Option<Direction> {
+        self.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InputQueue, ARROW_KEYS};
+    use crate::Direction;
+
+    #[test]
+    fn test_input_queue() {
+        let mut queue = InputQueue::new(2, ARROW_KEYS);
+        // Redundant and reversing presses are dropped.
+        queue.push(Direction::Right, Direction::Right);
+        queue.push(Direction::Left, Direction::Right);
+        assert_eq!(None, queue.pop());
+
+        // A quick U-turn is kept in order, and presses past the depth are dropped.
+        queue.push(Direction::Up, Direction::Right);
+        queue.push(Direction::Up, Direction::Right);
+        queue.push(Direction::Left, Direction::Right);
+        queue.push(Direction::Down, Direction::Right);
+        assert_eq!(Some(Direction::Up), queue.pop());
+        assert_eq!(Some(Direction::Left), queue.pop());
+        assert_eq!(None, queue.pop());
+    }
+}
@@ -0,0 +1,162 @@
+//! Rules given at launch.
+//!
+//! * Native: `--level <file>`, `--wrap`, `--length <n>`, `--fruits <n>`, `--versus`, and
+//!   `--seed <n>` arguments.
+//! * WASM: `wrap=true`, `length=<n>`, `fruits=<n>`, `versus=true`, and `seed=<n>` URL query params.
+use crate::rules::SnakeRules;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct LaunchArgs {
+    pub(crate) rules: SnakeRules,
+    /// Seed of the first game, to play the same fruit as someone else. Random if `None`.
+    pub(crate) opt_seed: Option<u64>,
+}
+
+/// Default rules, changed by whatever was given at launch.
+pub(crate) fn launch_args() -> Result<LaunchArgs, String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let args = parse_cli_args(std::env::args().skip(1), |path| {
+        std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))
+    })?;
+    #[cfg(target_arch = "wasm32")]
+    let args = parse_query_params(better_quad::bq::url_query_params())?;
+
+    args.rules.validate()?;
+    Ok(args)
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+fn parse_cli_args(
+    mut args: impl Iterator<Item = String>,
+    read_file: impl Fn(&str) -> Result<String, String>,
+) -> Result<LaunchArgs, String> {
+    // The level replaces the whole board, so other args are applied after it.
+    let mut opt_level = None;
+    let mut wrap_around = false;
+    let mut versus = false;
+    let mut opt_length = None;
+    let mut opt_num_fruits = None;
+    let mut opt_seed = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--level" => {
+                let path = args.next().ok_or("--level requires a file path")?;
+                let level = SnakeRules::from_level(&read_file(&path)?)
+                    .map_err(|e| format!("{path}: {e}"))?;
+                opt_level = Some(level);
+            }
+            "--wrap" => wrap_around = true,
+            "--versus" => versus = true,
+            "--length" => {
+                let value = args.next().ok_or("--length requires a number")?;
+                opt_length = Some(parse_count(&value, "snake length")?);
+            }
+            "--fruits" => {
+                let value = args.next().ok_or("--fruits requires a number")?;
+                opt_num_fruits = Some(parse_count(&value, "number of fruits")?);
+            }
+            "--seed" => {
+                let value = args.next().ok_or("--seed requires a number")?;
+                opt_seed = Some(parse_seed(&value)?);
+            }
+            _ => {
+                return Err(format!(
+                    "unknown argument '{arg}' \
+                    (expected --level, --wrap, --length, --fruits, --versus, or --seed)"
+                ))
+            }
+        }
+    }
+
+    let mut rules = opt_level.unwrap_or_default().with_wrap_around(wrap_around);
+    if let Some(length) = opt_length {
+        rules = rules.with_initial_length(length);
+    }
+    if let Some(num_fruits) = opt_num_fruits {
+        rules = rules.with_num_fruits(num_fruits);
+    }
+    if versus {
+        rules = rules.with_num_players(2);
+    }
+    Ok(LaunchArgs { rules, opt_seed })
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+fn parse_query_params(params: Vec<(String, String)>) -> Result<LaunchArgs, String> {
+    let mut rules = SnakeRules::default();
+    let mut opt_seed = None;
+    // Ignore other params, the page may be linked with unrelated ones.
+    for (key, value) in params {
+        match key.as_str() {
+            "wrap" => rules.wrap_around = value == "true",
+            "length" => rules.initial_length = parse_count(&value, "snake length")?,
+            "fruits" => rules.num_fruits = parse_count(&value, "number of fruits")?,
+            "versus" if value == "true" => rules.num_players = 2,
+            "seed" => opt_seed = Some(parse_seed(&value)?),
+            _ => {}
+        }
+    }
+    Ok(LaunchArgs { rules, opt_seed })
+}
+
+fn parse_count(value: &str, description: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{value}' isn't a {description}"))
+}
+
+pub(crate) fn parse_seed(value: &str) -> Result<u64, String> {
+    value.parse().map_err(|_| format!("'{value}' isn't a seed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_cli_args, LaunchArgs};
+    use crate::rules::SnakeRules;
+
+    #[test]
+    fn test_parse_cli_args() {
+        let read_file = |path: &str| match path {
+            "level.txt" => Ok("..\n.S\n".to_string()),
+            _ => Err(format!("{path}: No such file or directory")),
+        };
+        let cli = |args: &[&str]| parse_cli_args(args.iter().map(|a| a.to_string()), read_file);
+
+        assert_eq!(
+            Ok(LaunchArgs {
+                rules: SnakeRules::default(),
+                opt_seed: None
+            }),
+            cli(&[])
+        );
+        let LaunchArgs { rules, opt_seed } = cli(&[
+            "--wrap",
+            "--versus",
+            "--length",
+            "4",
+            "--level",
+            "level.txt",
+            "--fruits",
+            "2",
+            "--seed",
+            "1234",
+        ])
+        .unwrap();
+        assert_eq!((2, 2), (rules.board_width, rules.board_height));
+        assert_eq!((1, 1), rules.start);
+        assert!(rules.wrap_around);
+        assert_eq!(4, rules.initial_length);
+        assert_eq!(2, rules.num_fruits);
+        assert_eq!(2, rules.num_players);
+        assert_eq!(Some(1234), opt_seed);
+
+        assert_eq!(
+            Err("'x' isn't a snake length".to_string()),
+            cli(&["--length", "x"])
+        );
+        assert_eq!(
+            Err("missing.txt: No such file or directory".to_string()),
+            cli(&["--level", "missing.txt"])
+        );
+    }
+}
@@ -1,42 +1,187 @@
-use better_quad::bq::TextAlignment;
+use crate::ai::PathfindingAi;
+pub use crate::ai::{benchmark, AiBenchmark};
+use crate::controller::Controller;
+use crate::game::Game;
+use crate::high_scores::{HighScore, HighScores};
+use crate::input_queue::{DirectionKeys, InputQueue, ARROW_KEYS, WASD_KEYS};
+use crate::menu::{Menu, MenuAction};
+use crate::render::BoardLayout;
+use crate::replay::{Ghost, RunRecord, Turn};
+pub use crate::rules::SnakeRules;
+use better_quad::bq::{BetterKeyCode, TextAlignment, TextAnchorPoint, TextBackground};
 use better_quad::{bq, mq};
 use better_quad::{
     bq::{FpsCounter, Timestamp},
     StatefulGui,
 };
-use std::collections::LinkedList;
 use std::ops::Add;
 use std::time::Duration;
 
-const SQUARES: i16 = 16;
+mod ai;
+mod controller;
+mod fruit;
+mod game;
+mod high_scores;
+mod input_queue;
+mod launch_args;
+mod menu;
+mod render;
+mod replay;
+mod rules;
 
-/// Tick movement every period
-const INITIAL_MOVEMENT_TICK_SPEED: Duration = Duration::from_millis(200);
-/// Gradually move faster.
-/// Current config = `[0.2, 0.18, 0.162, 0.1458, ...]`
-const MOVEMENT_TICK_SPEED_MULTIPLICATIVE_FACTOR: f64 = 0.9;
+/// Direction presses buffered ahead of the movement ticks.
+const DEFAULT_INPUT_QUEUE_DEPTH: usize = 3;
 
-type Point = (i16, i16);
+// Menu keys are in `MenuAction::key`.
+const KEY_PAUSE: mq::KeyCode = mq::KeyCode::Escape;
+const KEY_COPY_SEED: mq::KeyCode = mq::KeyCode::C;
+
+/// Counted down before movement starts or resumes, to get ready.
+const COUNTDOWN_SECS: u64 = 3;
+/// Idle time on the title screen before the computer starts playing a demo.
+const DEMO_IDLE_DURATION: Duration = Duration::from_secs(15);
+
+/// Long enough for any `u64`.
+const MAX_SEED_DIGITS: usize = 20;
+const STATUS_FONT_SIZE: u16 = 22;
+const STATUS_DISPLAY_DURATION: Duration = Duration::from_secs(4);
+const SEED_FONT_SIZE: u16 = 20;
+const HUD_FONT_SIZE: u16 = 28;
+const TITLE_FONT_SIZE: u16 = 60;
+const GAME_OVER_FONT_SIZE: u16 = 30;
+const HIGH_SCORES_FONT_SIZE: u16 = 24;
+const COUNTDOWN_FONT_SIZE: u16 = 120;
+/// Head and body colors of each player's snake.
+const SNAKE_COLORS: [(mq::Color, mq::Color); rules::MAX_PLAYERS] =
+    [(mq::DARKGREEN, mq::LIME), (mq::DARKBLUE, mq::BLUE)];
+/// Space between the menu column and the high scores next to it.
+const COLUMN_GAP: f32 = 20.0;
+const TEXT_PADDING: f32 = 6.0;
+/// Where exported runs are written on native. WASM only has the clipboard.
+#[cfg(not(target_arch = "wasm32"))]
+const RUN_EXPORT_FILE: &str = "caterpillar-run.txt";
+
+/// Board cell, `(x, y)` from the top left.
+pub type Point = (i16, i16);
 
 pub struct SnakeGameState {
-    snake: Snake,
-    fruit: Point,
-    score: u64,
-    movement_tick_speed: Duration,
+    screen: Screen,
+    /// Buttons for the current screen.
+    menu: Menu,
+    game: Game,
+    mode: Mode,
+    input_queue_depth: usize,
+    /// One per snake, from `mode`.
+    controllers: Vec<Box<dyn Controller>>,
+    /// Versus rounds won by each player, since versus was started from the title screen.
+    round_wins: Vec<u32>,
+    /// This run so far, to export.
+    record: RunRecord,
+    /// An imported run played back alongside this one, with the same seed.
+    opt_ghost: Option<Ghost>,
+    /// Digits typed so far, while entering the seed of the next game.
+    opt_seed_entry: Option<String>,
+    high_scores: HighScores,
+    /// Name typed so far, while entering a new high score after game over.
+    opt_name_entry: Option<String>,
+    /// Where this game's score landed in `high_scores`, to highlight it.
+    opt_high_score_rank: Option<usize>,
+    // Feedback for import/export, and when it was set.
+    status_text: Option<(String, Timestamp)>,
     last_update: Timestamp,
-    game_over: bool,
+    /// When the last movement tick was played, since movement started or resumed.
+    opt_last_tick: Option<Timestamp>,
+    /// Last key press, click, or mouse movement on the title screen, to start the demo when idle.
+    last_title_input: Timestamp,
     fps_counter: FpsCounter,
 }
 
-struct Snake {
-    head: Point,
-    body: LinkedList<Point>,
-    next_dir: Direction,
-    queued_dir: Option<Direction>,
-    next_dir_locked: bool,
+/// Who steers which snake.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Mode {
+    Single,
+    /// Two players on the keyboard.
+    Versus,
+    /// The player against the computer.
+    VersusComputer,
+    /// The computer playing on its own, started from the title screen.
+    Demo,
+}
+
+impl Mode {
+    fn num_players(self) -> usize {
+        match self {
+            Self::Single | Self::Demo => 1,
+            Self::Versus | Self::VersusComputer => 2,
+        }
+    }
+
+    /// Single players turn with the arrow keys. In versus, player 1 (starting top left) has WASD,
+    /// and player 2 the arrow keys.
+    fn controllers(self, input_queue_depth: usize) -> Vec<Box<dyn Controller>> {
+        let keys = |keys: DirectionKeys| -> Box<dyn Controller> {
+            Box::new(InputQueue::new(input_queue_depth, keys))
+        };
+        match self {
+            Self::Single => vec![keys(ARROW_KEYS)],
+            Self::Versus => vec![keys(WASD_KEYS), keys(ARROW_KEYS)],
+            Self::VersusComputer => vec![keys(ARROW_KEYS), Box::new(PathfindingAi)],
+            Self::Demo => vec![Box::new(PathfindingAi)],
+        }
+    }
+
+    /// Versus players, with their keys.
+    fn player_name(self, player: usize) -> &'static str {
+        match (self, player) {
+            (Self::VersusComputer, 0) => "You",
+            (Self::VersusComputer, _) => "Computer",
+            (_, 0) => "Player 1 (wasd)",
+            (_, _) => "Player 2 (arrows)",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Screen {
+    Title,
+    /// Counting down to movement starting or resuming, from when the count started.
+    Countdown {
+        started: Timestamp,
+    },
+    Playing,
+    Paused,
+    GameOver,
 }
 
-#[derive(Copy, Clone, PartialEq)]
+impl Screen {
+    fn menu_actions(self) -> &'static [MenuAction] {
+        match self {
+            Self::Title => &[
+                MenuAction::Play,
+                MenuAction::Versus,
+                MenuAction::VersusComputer,
+                MenuAction::Demo,
+                MenuAction::EnterSeed,
+                MenuAction::ImportRun,
+            ],
+            Self::Countdown { .. } | Self::Playing => &[],
+            Self::Paused => &[
+                MenuAction::Resume,
+                MenuAction::RetrySeed,
+                MenuAction::TitleMenu,
+            ],
+            Self::GameOver => &[
+                MenuAction::PlayAgain,
+                MenuAction::RetrySeed,
+                MenuAction::EnterSeed,
+                MenuAction::ExportRun,
+                MenuAction::TitleMenu,
+            ],
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
 enum Direction {
     Right,
     Left,
@@ -44,6 +189,17 @@ enum Direction {
     Down,
 }
 
+impl Direction {
+    fn opposite(self) -> Self {
+        match self {
+            Direction::Right => Direction::Left,
+            Direction::Left => Direction::Right,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+        }
+    }
+}
+
 impl Add<Direction> for Point {
     type Output = Self;
 
@@ -59,25 +215,326 @@ impl Add<Direction> for Point {
 
 impl Default for SnakeGameState {
     fn default() -> Self {
+        Self::new(SnakeRules::default()).expect("Default rules are valid")
+    }
+}
+
+impl SnakeGameState {
+    /// A game with a random seed.
+    pub fn new(rules: SnakeRules) -> Result<Self, String> {
+        rules.validate()?;
+        Ok(Self::started(rules, random_seed(), high_scores::load()))
+    }
+
+    /// On the title screen, with `high_scores` already loaded. `rules` must already be validated.
+    fn started(rules: SnakeRules, seed: u64, high_scores: HighScores) -> Self {
+        let num_players = rules.num_players;
+        let mode = if num_players > 1 {
+            Mode::Versus
+        } else {
+            Mode::Single
+        };
         Self {
-            snake: Snake {
-                head: (0, 0),
-                body: LinkedList::new(),
-                next_dir: Direction::Right,
-                queued_dir: None,
-                next_dir_locked: false,
-            },
-            fruit: (
-                mq::rand::gen_range(0, SQUARES),
-                mq::rand::gen_range(0, SQUARES),
-            ),
-            score: 0,
-            movement_tick_speed: INITIAL_MOVEMENT_TICK_SPEED,
+            screen: Screen::Title,
+            menu: Menu::new(Screen::Title.menu_actions()),
+            record: RunRecord::new(rules.clone(), seed),
+            game: Game::new(rules, seed),
+            mode,
+            input_queue_depth: DEFAULT_INPUT_QUEUE_DEPTH,
+            controllers: mode.controllers(DEFAULT_INPUT_QUEUE_DEPTH),
+            round_wins: vec![0; num_players],
+            opt_ghost: None,
+            opt_seed_entry: None,
+            high_scores,
+            opt_name_entry: None,
+            opt_high_score_rank: None,
+            status_text: None,
             last_update: Timestamp::now(),
-            game_over: false,
+            opt_last_tick: None,
+            last_title_input: Timestamp::now(),
             fps_counter: FpsCounter::new(),
         }
     }
+
+    /// Rules given at launch (see the README). Invalid ones are logged, and the default rules used.
+    pub fn from_launch_args() -> Self {
+        match launch_args::launch_args() {
+            Ok(args) => {
+                let state = Self::started(args.rules, random_seed(), high_scores::load());
+                match args.opt_seed {
+                    Some(seed) => state.with_seed(seed),
+                    None => state,
+                }
+            }
+            Err(e) => {
+                mq::error!("Invalid launch args, using the default rules: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    /// Buffer up to `depth` direction presses ahead of the movement ticks. 1 only remembers the
+    /// next turn, while deeper queues let quick sequences (like a U-turn) all apply.
+    pub fn with_input_queue_depth(mut self, depth: usize) -> Self {
+        self.input_queue_depth = depth;
+        self.controllers = self.mode.controllers(depth);
+        self
+    }
+
+    /// `mode` must have as many players as the rules.
+    fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+        self.controllers = mode.controllers(self.input_queue_depth);
+    }
+
+    /// Play the game seeded with `seed` instead, so fruit spawns the same as in other games with
+    /// that seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.game = Game::new(self.game.rules.clone(), seed);
+        self.record = RunRecord::new(self.game.rules.clone(), seed);
+        self
+    }
+
+    /// A new game with the same rules and settings, and no ghost, counting down to start.
+    fn restarted(&self, seed: u64, now: Timestamp) -> Self {
+        let mut state = Self::started(self.game.rules.clone(), seed, self.high_scores.clone())
+            .with_input_queue_depth(self.input_queue_depth);
+        state.set_mode(self.mode);
+        state.round_wins = self.round_wins.clone();
+        state.set_screen(Screen::Countdown { started: now });
+        state
+    }
+
+    /// Start playing from the title screen in `mode`, keeping the seed.
+    fn play(&mut self, mode: Mode, now: Timestamp) {
+        let num_players = mode.num_players();
+        if num_players != self.game.snakes.len() {
+            let rules = self.game.rules.clone().with_num_players(num_players);
+            if let Err(e) = rules.validate() {
+                self.set_status_text(
+                    format!("Can't play this board with {num_players}: {e}"),
+                    now,
+                );
+                return;
+            }
+            let status_text = self.status_text.take();
+            *self = Self::started(rules, self.game.seed, self.high_scores.clone())
+                .with_input_queue_depth(self.input_queue_depth);
+            self.status_text = status_text;
+        }
+        self.set_mode(mode);
+        self.set_screen(Screen::Countdown { started: now });
+    }
+
+    /// The computer plays on its own, without a countdown, until any input.
+    fn start_demo(&mut self, now: Timestamp) {
+        self.play(Mode::Demo, now);
+        if self.mode == Mode::Demo {
+            self.last_update = now;
+            self.set_screen(Screen::Playing);
+        }
+    }
+
+    /// Play the same seed again, racing the same ghost if there is one.
+    fn retried(&self, now: Timestamp) -> Self {
+        let mut state = self.restarted(self.game.seed, now);
+        state.opt_ghost = self.opt_ghost.as_ref().map(|ghost| ghost.restarted(now));
+        state
+    }
+
+    /// Back to the title screen, with the same rules and settings, and a new seed.
+    fn to_title(&self) -> Self {
+        let mut state = Self::started(
+            self.game.rules.clone(),
+            random_seed(),
+            self.high_scores.clone(),
+        )
+        .with_input_queue_depth(self.input_queue_depth);
+        state.status_text = self.status_text.clone();
+        state
+    }
+
+    fn set_screen(&mut self, screen: Screen) {
+        self.screen = screen;
+        self.opt_last_tick = None;
+        self.menu = Menu::new(screen.menu_actions());
+    }
+
+    fn apply_menu_action(&mut self, action: MenuAction, now: Timestamp) {
+        match action {
+            MenuAction::Play => self.play(Mode::Single, now),
+            MenuAction::Versus => self.play(Mode::Versus, now),
+            MenuAction::VersusComputer => self.play(Mode::VersusComputer, now),
+            MenuAction::Demo => self.start_demo(now),
+            MenuAction::Resume => self.set_screen(Screen::Countdown { started: now }),
+            MenuAction::PlayAgain => *self = self.restarted(random_seed(), now),
+            MenuAction::RetrySeed => *self = self.retried(now),
+            MenuAction::EnterSeed => {
+                // Skip the key's own char.
+                while mq::get_char_pressed().is_some() {}
+                self.opt_seed_entry = Some(String::new());
+            }
+            MenuAction::ExportRun => self.export_run(now),
+            MenuAction::ImportRun => match mq::miniquad::window::clipboard_get() {
+                Some(text) => self.import_run(&text, now),
+                None => self.set_status_text("Clipboard is empty", now),
+            },
+            MenuAction::TitleMenu => *self = self.to_title(),
+        }
+    }
+
+    /// Turn to whatever the controllers say, and move the snakes a cell. Turns are recorded to
+    /// replay the run later.
+    fn tick_movement(&mut self) {
+        let game = &self.game;
+        let turns = self
+            .controllers
+            .iter_mut()
+            .enumerate()
+            .map(|(snake_index, controller)| controller.next_turn(game, snake_index))
+            .collect::<Vec<_>>();
+        for (snake, opt_dir) in turns.iter().enumerate() {
+            if let Some(dir) = *opt_dir {
+                let tick = self.game.num_ticks;
+                self.record.turns.push(Turn { tick, snake, dir });
+            }
+        }
+        self.game.tick(&turns);
+
+        if !self.game.game_over {
+            return;
+        }
+        if self.mode == Mode::Demo {
+            *self = self.to_title();
+            return;
+        }
+        self.set_screen(Screen::GameOver);
+        if self.is_versus() {
+            if let Some(winner) = self.game.round_winner() {
+                self.round_wins[winner] += 1;
+            }
+        } else if self.high_scores.qualifies(self.game.snakes[0].score) {
+            // Start from the last name entered, most likely the same person.
+            self.opt_name_entry = Some(high_scores::load_saved_player_name());
+        }
+    }
+
+    /// High scores are only for single player games.
+    fn is_versus(&self) -> bool {
+        self.game.snakes.len() > 1
+    }
+
+    fn set_status_text(&mut self, text: impl Into<String>, now: Timestamp) {
+        self.status_text = Some((text.into(), now));
+    }
+
+    /// Copy the seed, and import dropped runs. Exporting and importing from the clipboard are in
+    /// the menus.
+    fn update_run_keys(&mut self, now: Timestamp) {
+        if mq::is_key_pressed(KEY_COPY_SEED) {
+            mq::miniquad::window::clipboard_set(&self.game.seed.to_string());
+            self.set_status_text(format!("Copied seed {}", self.game.seed), now);
+        }
+
+        // Dropping a run file onto the window also loads it
+        for dropped_file in mq::get_dropped_files() {
+            match dropped_file.bytes.map(String::from_utf8) {
+                Some(Ok(text)) => self.import_run(&text, now),
+                _ => self.set_status_text("Dropped file isn't a text run record", now),
+            }
+        }
+    }
+
+    fn export_run(&mut self, now: Timestamp) {
+        let record_text = self.record.to_text();
+        mq::miniquad::window::clipboard_set(&record_text);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(e) = std::fs::write(RUN_EXPORT_FILE, &record_text) {
+            self.set_status_text(format!("Copied run, but failed to write file: {e}"), now);
+            return;
+        }
+
+        self.set_status_text("Copied run to clipboard", now);
+    }
+
+    /// Start a new game with the run's rules and seed, racing the run as a ghost.
+    fn import_run(&mut self, text: &str, now: Timestamp) {
+        match RunRecord::parse(text) {
+            Ok(record) => {
+                let mut state =
+                    Self::started(record.rules.clone(), record.seed, self.high_scores.clone())
+                        .with_input_queue_depth(self.input_queue_depth);
+                state.set_screen(Screen::Countdown { started: now });
+                state.opt_ghost = Some(Ghost::new(record, now));
+                *self = state;
+                self.set_status_text("Loaded run, racing its ghost", now);
+            }
+            Err(e) => self.set_status_text(format!("Invalid run: {e}"), now),
+        }
+    }
+
+    /// Type digits, [backspace] to fix, [enter] to play, or [escape] to cancel.
+    fn update_seed_entry(&mut self, now: Timestamp) {
+        let Some(seed_text) = &mut self.opt_seed_entry else {
+            return;
+        };
+        type_text(seed_text, MAX_SEED_DIGITS, |c| c.is_ascii_digit());
+        if mq::is_key_pressed(mq::KeyCode::Escape) {
+            self.opt_seed_entry = None;
+        } else if mq::is_key_pressed(mq::KeyCode::Enter) {
+            match launch_args::parse_seed(seed_text) {
+                Ok(seed) => *self = self.restarted(seed, now),
+                Err(e) => self.set_status_text(e, now),
+            }
+        }
+    }
+
+    /// Type a name, [enter] to save the high score, or [escape] to skip it.
+    fn update_name_entry(&mut self, now: Timestamp) {
+        let Some(name) = &mut self.opt_name_entry else {
+            return;
+        };
+        type_text(name, high_scores::MAX_PLAYER_NAME_LEN, |c| !c.is_control());
+        if mq::is_key_pressed(mq::KeyCode::Escape) {
+            self.opt_name_entry = None;
+        } else if mq::is_key_pressed(mq::KeyCode::Enter) {
+            let name = name.trim().to_string();
+            let name = if name.is_empty() {
+                "Anonymous".to_string()
+            } else {
+                high_scores::save_player_name(&name);
+                name
+            };
+            self.opt_name_entry = None;
+            self.opt_high_score_rank = self
+                .high_scores
+                .insert(HighScore::capture(&self.game, name, now));
+            if let Err(e) = high_scores::save(&self.high_scores) {
+                self.set_status_text(format!("Failed to save high score: {e}"), now);
+            }
+        }
+    }
+}
+
+/// Add chars typed this frame that are `allowed`, up to `max_len` chars, and [backspace] deletes.
+fn type_text(text: &mut String, max_len: usize, allowed: impl Fn(char) -> bool) {
+    while let Some(c) = mq::get_char_pressed() {
+        if allowed(c) && text.chars().count() < max_len {
+            text.push(c);
+        }
+    }
+    if mq::is_key_pressed(mq::KeyCode::Backspace) {
+        text.pop();
+    }
+}
+
+/// Seed for a new game, drawn from the global generator (seeded at launch) so quick restarts don't
+/// repeat seeds. Also set as the bq_rand seed, so it's what `bq::get_last_set_rand_seed` reports.
+fn random_seed() -> u64 {
+    let seed = (u64::from(mq::rand::rand()) << 32) | u64::from(mq::rand::rand());
+    bq::set_rand_seed(seed);
+    seed
 }
 
 impl StatefulGui for SnakeGameState {
@@ -93,211 +550,291 @@ impl StatefulGui for SnakeGameState {
 fn evaluate_game(state: &mut SnakeGameState, now: Timestamp) {
     state.fps_counter.tick_frame(now);
 
-    if state.game_over {
-        if mq::is_key_down(mq::KeyCode::Enter) {
-            *state = SnakeGameState::default();
-        }
-        return;
+    if state
+        .status_text
+        .as_ref()
+        .is_some_and(|(_, set_time)| now - *set_time > STATUS_DISPLAY_DURATION)
+    {
+        state.status_text = None;
     }
 
-    // game_over == false
-
-    let dir_key_down = get_dir_key_down();
-    if !state.snake.next_dir_locked {
-        // check for change direction
-        match dir_key_down {
-            Some(Direction::Right) => {
-                if state.snake.next_dir != Direction::Left {
-                    state.snake.next_dir = Direction::Right;
-                    state.snake.next_dir_locked = true;
-                }
-            }
-            Some(Direction::Left) => {
-                if state.snake.next_dir != Direction::Right {
-                    state.snake.next_dir = Direction::Left;
-                    state.snake.next_dir_locked = true;
-                }
-            }
-            Some(Direction::Up) => {
-                if state.snake.next_dir != Direction::Down {
-                    state.snake.next_dir = Direction::Up;
-                    state.snake.next_dir_locked = true;
-                }
-            }
-            Some(Direction::Down) => {
-                if state.snake.next_dir != Direction::Up {
-                    state.snake.next_dir = Direction::Down;
-                    state.snake.next_dir_locked = true;
-                }
-            }
-            None => {}
+    if state.screen == Screen::Title {
+        let any_input = mq::get_last_key_pressed().is_some()
+            || mq::is_mouse_button_pressed(mq::MouseButton::Left)
+            || mq::mouse_delta_position() != mq::Vec2::ZERO;
+        if any_input {
+            state.last_title_input = now;
+        } else if state.opt_seed_entry.is_none()
+            && now - state.last_title_input > DEMO_IDLE_DURATION
+        {
+            state.start_demo(now);
+            return;
         }
     }
 
-    // Store the queued direction if the next move is already locked in
-    if state.snake.next_dir_locked {
-        match dir_key_down {
-            Some(Direction::Right) => {
-                // TODO refactor both this and above to not allow re-sending the same input (QoL)
-                if state.snake.next_dir != Direction::Left {
-                    state.snake.queued_dir = Some(Direction::Right);
-                }
+    // Typing takes over the keys until it's done.
+    if state.opt_name_entry.is_some() {
+        state.update_name_entry(now);
+        return;
+    }
+    if state.opt_seed_entry.is_some() {
+        state.update_seed_entry(now);
+        return;
+    }
+    state.update_run_keys(now);
+
+    match state.screen {
+        Screen::Title | Screen::Paused | Screen::GameOver => {
+            let (menu_x, menu_y) = menu_position(state.screen, &state.menu);
+            if let Some(action) = state.menu.update(menu_x, menu_y) {
+                state.apply_menu_action(action, now);
+            } else if state.screen == Screen::Paused && mq::is_key_pressed(KEY_PAUSE) {
+                state.apply_menu_action(MenuAction::Resume, now);
             }
-            Some(Direction::Left) => {
-                if state.snake.next_dir != Direction::Right {
-                    state.snake.queued_dir = Some(Direction::Left);
+        }
+        Screen::Countdown { started } => {
+            if now - started >= Duration::from_secs(COUNTDOWN_SECS) {
+                // Start the movement period from now, not from before the countdown.
+                state.last_update = now;
+                if let Some(ghost) = &mut state.opt_ghost {
+                    ghost.resume(now);
                 }
+                state.set_screen(Screen::Playing);
             }
-            Some(Direction::Up) => {
-                if state.snake.next_dir != Direction::Down {
-                    state.snake.queued_dir = Some(Direction::Up);
-                }
+        }
+        Screen::Playing if state.mode == Mode::Demo => {
+            if mq::get_last_key_pressed().is_some()
+                || mq::is_mouse_button_pressed(mq::MouseButton::Left)
+            {
+                *state = state.to_title();
+                return;
             }
-            Some(Direction::Down) => {
-                if state.snake.next_dir != Direction::Up {
-                    state.snake.queued_dir = Some(Direction::Down);
-                }
+            if now - state.last_update > state.game.movement_tick_speed {
+                state.last_update = now;
+                state.opt_last_tick = Some(now);
+                state.tick_movement();
             }
-            None => {}
         }
-    }
+        Screen::Playing => {
+            if mq::is_key_pressed(KEY_PAUSE) {
+                state.set_screen(Screen::Paused);
+                return;
+            }
 
-    // apply movement if time has elapsed
-    if now - state.last_update > state.movement_tick_speed {
-        state.last_update = now;
-        state.snake.body.push_front(state.snake.head);
-        state.snake.head = state.snake.head + state.snake.next_dir;
-        if state.snake.head == state.fruit {
-            // Grow!
-            state.fruit = (
-                mq::rand::gen_range(0, SQUARES),
-                mq::rand::gen_range(0, SQUARES),
-            );
-            state.score += 100;
-            state.movement_tick_speed = Duration::from_secs_f64(
-                state.movement_tick_speed.as_secs_f64() * MOVEMENT_TICK_SPEED_MULTIPLICATIVE_FACTOR,
-            );
-        } else {
-            // Normal movement.
-            state.snake.body.pop_back();
-        }
+            if let Some(ghost) = &mut state.opt_ghost {
+                ghost.update(now);
+            }
 
-        // Apply queued dir, which is guaranteed to be valid compared to last applied dir.
-        if let Some(qd) = state.snake.queued_dir {
-            state.snake.next_dir = qd;
-            state.snake.queued_dir = None;
-        }
+            for (snake_index, controller) in state.controllers.iter_mut().enumerate() {
+                controller.update(&state.game, snake_index);
+            }
 
-        // Check for wall collision
-        if state.snake.head.0 < 0
-            || state.snake.head.1 < 0
-            || state.snake.head.0 >= SQUARES
-            || state.snake.head.1 >= SQUARES
-        {
-            state.game_over = true;
-        }
-        // Check for body collision
-        for (x, y) in &state.snake.body {
-            if *x == state.snake.head.0 && *y == state.snake.head.1 {
-                state.game_over = true;
+            // apply movement if time has elapsed
+            if now - state.last_update > state.game.movement_tick_speed {
+                state.last_update = now;
+                state.opt_last_tick = Some(now);
+                state.tick_movement();
             }
         }
-        state.snake.next_dir_locked = false;
     }
 }
 
-// TODO: actual key-down press queuing (like fancy mech keyboards) would be dope and feel really good.
-fn get_dir_key_down() -> Option<Direction> {
-    if mq::is_key_down(mq::KeyCode::Right) {
-        Some(Direction::Right)
-    } else if mq::is_key_down(mq::KeyCode::Left) {
-        Some(Direction::Left)
-    } else if mq::is_key_down(mq::KeyCode::Up) {
-        Some(Direction::Up)
-    } else if mq::is_key_down(mq::KeyCode::Down) {
-        Some(Direction::Down)
+/// Top left of the menu. Paused menus are centered over the board, and the others are left of the
+/// high scores.
+fn menu_position(screen: Screen, menu: &Menu) -> (f32, f32) {
+    if screen == Screen::Paused {
+        (
+            (mq::screen_width() - menu::BUTTON_WIDTH) / 2.,
+            (mq::screen_height() - menu.height()) / 2.,
+        )
     } else {
-        None
+        (
+            mq::screen_width() / 2. - COLUMN_GAP - menu::BUTTON_WIDTH,
+            column_top(),
+        )
     }
 }
 
-fn draw_game(state: &SnakeGameState) {
-    if !state.game_over {
-        // Draw game-in-progress state
-        mq::clear_background(mq::LIGHTGRAY);
-
-        let game_size = mq::screen_width().min(mq::screen_height());
-        // 20 = total padding (10 lrud)
-        let offset_x = (mq::screen_width() - game_size + 50.) / 2.;
-        let offset_y = (mq::screen_height() - game_size + 50.) / 2.;
-        let sq_size = (game_size - 50.) / SQUARES as f32;
-
-        mq::draw_rectangle(
-            offset_x,
-            offset_y,
-            game_size - 50.,
-            game_size - 50.,
-            mq::WHITE,
-        );
+/// Where the menu and high scores columns start on the title and game over screens.
+fn column_top() -> f32 {
+    mq::screen_height() * 0.35
+}
 
-        for i in 1..SQUARES {
-            mq::draw_line(
-                offset_x,
-                offset_y + sq_size * i as f32,
-                mq::screen_width() - offset_x,
-                offset_y + sq_size * i as f32,
-                2.,
-                mq::LIGHTGRAY,
+fn draw_game(state: &SnakeGameState) {
+    match state.screen {
+        Screen::Title => {
+            mq::clear_background(mq::WHITE);
+            bq::draw_text(
+                "Caterpillar",
+                TextAlignment::Center,
+                None,
+                TITLE_FONT_SIZE,
+                mq::DARKGREEN,
+                TextAnchorPoint::Center {
+                    x: mq::screen_width() / 2.,
+                    y: mq::screen_height() * 0.15,
+                },
+                None,
+            );
+            bq::draw_text(
+                format!(
+                    "Arrow keys to turn, [{}] to pause\n\
+                    Versus: player 1 on wasd, player 2 on arrows",
+                    KEY_PAUSE.to_lowercase()
+                ),
+                TextAlignment::Center,
+                None,
+                GAME_OVER_FONT_SIZE,
+                mq::DARKGRAY,
+                TextAnchorPoint::Center {
+                    x: mq::screen_width() / 2.,
+                    y: mq::screen_height() * 0.25,
+                },
+                None,
             );
+            draw_menu_or_prompt(state);
+            draw_high_scores(state);
         }
-
-        for i in 1..SQUARES {
-            mq::draw_line(
-                offset_x + sq_size * i as f32,
-                offset_y,
-                offset_x + sq_size * i as f32,
-                mq::screen_height() - offset_y,
-                2.,
-                mq::LIGHTGRAY,
+        Screen::Countdown { started } => {
+            draw_board(state);
+            draw_overlay();
+            let secs_left = COUNTDOWN_SECS.saturating_sub((Timestamp::now() - started).as_secs());
+            bq::draw_text(
+                secs_left.max(1).to_string(),
+                TextAlignment::Center,
+                None,
+                COUNTDOWN_FONT_SIZE,
+                mq::WHITE,
+                TextAnchorPoint::window_centered(),
+                None,
             );
         }
-
-        mq::draw_circle(
-            offset_x + state.snake.head.0 as f32 * sq_size,
-            offset_y + state.snake.head.1 as f32 * sq_size,
-            sq_size / 2.5,
-            mq::DARKGREEN,
-        );
-
-        for (x, y) in &state.snake.body {
-            mq::draw_circle(
-                offset_x + *x as f32 * sq_size,
-                offset_y + *y as f32 * sq_size,
-                sq_size / 2.5,
-                mq::LIME,
+        Screen::Playing => {
+            draw_board(state);
+            if state.mode == Mode::Demo {
+                bq::draw_text(
+                    "Demo, press any key",
+                    TextAlignment::Center,
+                    None,
+                    GAME_OVER_FONT_SIZE,
+                    mq::WHITE,
+                    TextAnchorPoint::Center {
+                        x: mq::screen_width() / 2.,
+                        y: GAME_OVER_FONT_SIZE as f32,
+                    },
+                    Some(TextBackground {
+                        color: mq::BLACK,
+                        x_padding: TEXT_PADDING,
+                        y_padding: TEXT_PADDING,
+                    }),
+                );
+            }
+        }
+        Screen::Paused => {
+            draw_board(state);
+            draw_overlay();
+            bq::draw_text(
+                "Paused",
+                TextAlignment::Center,
+                None,
+                TITLE_FONT_SIZE,
+                mq::WHITE,
+                TextAnchorPoint::Center {
+                    x: mq::screen_width() / 2.,
+                    y: (mq::screen_height() - state.menu.height()) / 2. - TITLE_FONT_SIZE as f32,
+                },
+                None,
+            );
+            state.menu.draw();
+        }
+        Screen::GameOver => {
+            let game = &state.game;
+            mq::clear_background(mq::WHITE);
+            let result_text = if state.is_versus() {
+                format!(
+                    "{}\n{}\nRounds: {}",
+                    match (state.mode, game.round_winner()) {
+                        (Mode::VersusComputer, Some(0)) => "You win the round!".to_string(),
+                        (mode, Some(winner)) =>
+                            format!("{} wins the round!", mode.player_name(winner)),
+                        (_, None) => "Draw!".to_string(),
+                    },
+                    game.snakes
+                        .iter()
+                        .enumerate()
+                        .map(|(i, snake)| format!("{}: {}", state.mode.player_name(i), snake.score))
+                        .collect::<Vec<_>>()
+                        .join("   "),
+                    format_round_wins(&state.round_wins),
+                )
+            } else {
+                let snake = &game.snakes[0];
+                format!(
+                    "{}\n\
+                    Score: {}   Length: {}\n\
+                    Time: {}   Max speed: {:.1} moves/s",
+                    if game.won {
+                        "You filled the board!"
+                    } else {
+                        "Game Over"
+                    },
+                    snake.score,
+                    snake.length(),
+                    format_duration(game.elapsed),
+                    game.max_speed(),
+                )
+            };
+            bq::draw_text(
+                result_text,
+                TextAlignment::Center,
+                None,
+                GAME_OVER_FONT_SIZE,
+                mq::DARKGRAY,
+                TextAnchorPoint::Center {
+                    x: mq::screen_width() / 2.,
+                    y: mq::screen_height() * 0.18,
+                },
+                None,
             );
+            draw_menu_or_prompt(state);
+            if !state.is_versus() {
+                draw_high_scores(state);
+            }
         }
+    }
 
-        mq::draw_circle(
-            offset_x + state.fruit.0 as f32 * sq_size,
-            offset_y + state.fruit.1 as f32 * sq_size,
-            sq_size / 2.5,
-            mq::GOLD,
-        );
-    } else {
-        // Draw game-over screen.
-        mq::clear_background(mq::WHITE);
+    // Text - import/export status
+    if let Some((status_text, _)) = &state.status_text {
         bq::draw_text(
-            "Game Over. Press [enter] to play again.",
+            status_text,
             TextAlignment::Left,
             None,
-            30,
-            mq::DARKGRAY,
-            bq::TextAnchorPoint::window_centered(),
-            None,
+            STATUS_FONT_SIZE,
+            mq::WHITE,
+            TextAnchorPoint::window_bottom_left(),
+            Some(TextBackground {
+                color: mq::BLACK,
+                x_padding: TEXT_PADDING,
+                y_padding: TEXT_PADDING,
+            }),
         );
     }
 
+    // Seed, to share with others
+    bq::draw_text(
+        format!("Seed: {}", state.game.seed),
+        TextAlignment::Left,
+        None,
+        SEED_FONT_SIZE,
+        mq::WHITE,
+        TextAnchorPoint::window_bottom_right(),
+        Some(TextBackground {
+            color: mq::BLACK,
+            x_padding: TEXT_PADDING,
+            y_padding: TEXT_PADDING,
+        }),
+    );
+
     // Unconditionally draw debug info
     mq::draw_text(
         format!("mqFPS: {}fps", mq::get_fps()).as_str(),
@@ -316,3 +853,289 @@ fn draw_game(state: &SnakeGameState) {
         mq::DARKGRAY,
     );
 }
+
+/// Dims the board under the pause menu and countdown.
+fn draw_overlay() {
+    mq::draw_rectangle(
+        0.,
+        0.,
+        mq::screen_width(),
+        mq::screen_height(),
+        mq::BLACK.with_alpha(0.5),
+    );
+}
+
+/// The menu, or what's being typed in its place.
+fn draw_menu_or_prompt(state: &SnakeGameState) {
+    let prompt_text = if let Some(name) = &state.opt_name_entry {
+        format!("New high score!\nName: {name}_\n\n[enter] to save\n[escape] to skip")
+    } else if let Some(seed_text) = &state.opt_seed_entry {
+        format!("Seed: {seed_text}_\n\n[enter] to play it\n[escape] to cancel")
+    } else {
+        state.menu.draw();
+        return;
+    };
+    let (x, y) = menu_position(state.screen, &state.menu);
+    bq::draw_text(
+        prompt_text,
+        TextAlignment::Left,
+        None,
+        GAME_OVER_FONT_SIZE,
+        mq::DARKGRAY,
+        TextAnchorPoint::TopLeft { x, y },
+        None,
+    );
+}
+
+/// The board mid-game: walls, obstacles, the snakes, fruit, and the score.
+fn draw_board(state: &SnakeGameState) {
+    let game = &state.game;
+    mq::clear_background(mq::LIGHTGRAY);
+
+    let rules = &game.rules;
+    let layout = BoardLayout::new(game);
+    let sq_size = layout.sq_size;
+    let (offset_x, offset_y) = (layout.offset_x, layout.offset_y);
+    let board_width = sq_size * rules.board_width as f32;
+    let board_height = sq_size * rules.board_height as f32;
+    mq::draw_rectangle(offset_x, offset_y, board_width, board_height, mq::WHITE);
+
+    for i in 1..rules.board_height {
+        mq::draw_line(
+            offset_x,
+            offset_y + sq_size * i as f32,
+            offset_x + board_width,
+            offset_y + sq_size * i as f32,
+            2.,
+            mq::LIGHTGRAY,
+        );
+    }
+
+    for i in 1..rules.board_width {
+        mq::draw_line(
+            offset_x + sq_size * i as f32,
+            offset_y,
+            offset_x + sq_size * i as f32,
+            offset_y + board_height,
+            2.,
+            mq::LIGHTGRAY,
+        );
+    }
+
+    // Solid walls get a border. Wrapping boards don't have walls.
+    if !rules.wrap_around {
+        mq::draw_rectangle_lines(
+            offset_x,
+            offset_y,
+            board_width,
+            board_height,
+            4.,
+            mq::DARKGRAY,
+        );
+    }
+
+    for obstacle in &rules.obstacles {
+        let corner = layout.cell_corner(*obstacle);
+        mq::draw_rectangle(corner.x, corner.y, sq_size, sq_size, mq::DARKGRAY);
+    }
+
+    let now = Timestamp::now();
+    // The imported run being raced, faded under the live snake. It keeps going after it dies.
+    if let Some(ghost) = &state.opt_ghost {
+        let progress =
+            render::tick_progress(ghost.opt_last_tick, ghost.game.movement_tick_speed, now);
+        let ghost_color = mq::DARKPURPLE.with_alpha(0.25);
+        for snake in &ghost.game.snakes {
+            render::draw_snake(&layout, snake, progress, ghost_color, ghost_color);
+        }
+    }
+
+    let progress = render::tick_progress(state.opt_last_tick, game.movement_tick_speed, now);
+    for (i, snake) in game.snakes.iter().enumerate() {
+        let (head_color, body_color) = SNAKE_COLORS[i];
+        // See-through while in ghost mode.
+        let snake_alpha = if snake.ghost_ticks_left > 0 { 0.4 } else { 1.0 };
+        render::draw_snake(
+            &layout,
+            snake,
+            progress,
+            head_color.with_alpha(snake_alpha),
+            body_color.with_alpha(snake_alpha),
+        );
+    }
+
+    for fruit in &game.fruits {
+        // Special fruits shrink as they're about to disappear.
+        let size_fraction = fruit.opt_ticks_left.map_or(1.0, |ticks_left| {
+            0.5 + 0.5 * ticks_left as f32 / fruit::SPECIAL_FRUIT_TICKS as f32
+        });
+        let center = layout.cell_center(mq::vec2(fruit.point.0 as f32, fruit.point.1 as f32));
+        mq::draw_circle(
+            center.x,
+            center.y,
+            sq_size / 2.5 * size_fraction,
+            fruit.kind.color(),
+        );
+    }
+
+    let hud_text = if state.is_versus() {
+        let scores = game
+            .snakes
+            .iter()
+            .enumerate()
+            .map(|(i, snake)| format!("{}: {}", state.mode.player_name(i), snake.score));
+        let rounds = format!("Rounds: {}", format_round_wins(&state.round_wins));
+        scores
+            .chain(std::iter::once(rounds))
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        let snake = &game.snakes[0];
+        format!("Score: {}\nLength: {}", snake.score, snake.length())
+    };
+    bq::draw_text(
+        hud_text,
+        TextAlignment::Right,
+        None,
+        HUD_FONT_SIZE,
+        mq::WHITE,
+        TextAnchorPoint::window_top_right(),
+        Some(TextBackground {
+            color: mq::DARKGRAY,
+            x_padding: TEXT_PADDING,
+            y_padding: TEXT_PADDING,
+        }),
+    );
+}
+
+/// Top 10 right of the menu, with this game's entry marked.
+fn draw_high_scores(state: &SnakeGameState) {
+    let entries = &state.high_scores.entries;
+    let text = if entries.is_empty() {
+        "No high scores yet".to_string()
+    } else {
+        std::iter::once("High scores".to_string())
+            .chain(entries.iter().enumerate().map(|(i, entry)| {
+                format!(
+                    "{}{}. {}  {}  (length {}, {})",
+                    if state.opt_high_score_rank == Some(i) {
+                        "> "
+                    } else {
+                        ""
+                    },
+                    i + 1,
+                    entry.name,
+                    entry.score,
+                    entry.length,
+                    format_duration(Duration::from_millis(entry.duration_ms)),
+                )
+            }))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    bq::draw_text(
+        text,
+        TextAlignment::Left,
+        None,
+        HIGH_SCORES_FONT_SIZE,
+        mq::DARKGRAY,
+        TextAnchorPoint::TopLeft {
+            x: mq::screen_width() / 2. + COLUMN_GAP,
+            y: column_top(),
+        },
+        None,
+    );
+}
+
+/// `2 - 1`, in player order.
+fn format_round_wins(round_wins: &[u32]) -> String {
+    round_wins
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(" - ")
+}
+
+/// `m:ss`
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::high_scores::HighScores;
+    use crate::input_queue::{InputQueue, ARROW_KEYS, WASD_KEYS};
+    use crate::replay::Turn;
+    use crate::{Direction, Mode, Screen, SnakeGameState, SnakeRules};
+    use better_quad::bq::Timestamp;
+
+    #[test]
+    fn test_turns_are_recorded() {
+        let mut state = SnakeGameState::new(SnakeRules::default())
+            .unwrap()
+            .with_seed(1234);
+        assert_eq!(1234, state.record.seed);
+
+        state.tick_movement();
+        let mut input_queue = InputQueue::new(1, ARROW_KEYS);
+        input_queue.push(Direction::Down, state.game.snakes[0].next_dir);
+        state.controllers[0] = Box::new(input_queue);
+        state.tick_movement();
+        state.tick_movement();
+        let turn = Turn {
+            tick: 1,
+            snake: 0,
+            dir: Direction::Down,
+        };
+        assert_eq!(vec![turn], state.record.turns);
+        assert_eq!((1, 2), state.game.snakes[0].head);
+    }
+
+    #[test]
+    fn test_game_over_screen() {
+        let rules = SnakeRules::from_level("S.").unwrap();
+        let mut state = SnakeGameState::started(rules, 1, HighScores::default());
+        assert_eq!(Screen::Title, state.screen);
+
+        state.tick_movement();
+        assert_eq!(Screen::GameOver, state.screen);
+        // Scoring asks for a name for the high scores.
+        assert!(state.opt_name_entry.is_some());
+    }
+
+    #[test]
+    fn test_demo_back_to_title() {
+        let rules = SnakeRules::from_level("S.").unwrap();
+        let mut state = SnakeGameState::new(rules).unwrap();
+        state.start_demo(Timestamp::now());
+        assert_eq!((Mode::Demo, Screen::Playing), (state.mode, state.screen));
+
+        // The computer fills the board, and nobody's asked for a name.
+        state.tick_movement();
+        assert_eq!((Mode::Single, Screen::Title), (state.mode, state.screen));
+        assert!(state.opt_name_entry.is_none());
+    }
+
+    #[test]
+    fn test_versus_round_wins() {
+        // Player 2 starts at the mirrored cell, heading left into the obstacle.
+        let rules = SnakeRules::from_level("S...\n....\n..#.\n").unwrap();
+        let mut state = SnakeGameState::new(rules).unwrap();
+        state.play(Mode::Versus, Timestamp::now());
+        assert!(state.is_versus());
+        state.tick_movement();
+        assert_eq!(Screen::GameOver, state.screen);
+        assert_eq!(vec![1, 0], state.round_wins);
+        assert!(state.opt_name_entry.is_none());
+    }
+
+    #[test]
+    fn test_game_over_keys_arent_steering_keys() {
+        for action in Screen::GameOver.menu_actions() {
+            let is_steering_key = |(key, _)| key == action.key();
+            assert!(!WASD_KEYS.into_iter().any(is_steering_key), "{action:?}");
+            assert!(!ARROW_KEYS.into_iter().any(is_steering_key), "{action:?}");
+        }
+    }
+}
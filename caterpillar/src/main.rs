@@ -8,5 +8,5 @@ fn window_conf() -> mq::Conf {
 #[macroquad::main(window_conf)]
 async fn main() {
     better_quad::initialize_engine();
-    better_quad::run_gui_default::<SnakeGameState>().await
+    better_quad::run_gui(SnakeGameState::from_launch_args()).await
 }
@@ -0,0 +1,122 @@
+//! Buttons for the title, pause, and game over screens. Each button also has a key.
+use better_quad::bq::{BetterKeyCode, SimpleButton};
+use better_quad::mq;
+
+pub(crate) const BUTTON_WIDTH: f32 = 260.0;
+const BUTTON_HEIGHT: f32 = 40.0;
+const BUTTON_GAP: f32 = 10.0;
+const BUTTON_FONT_SIZE: u16 = 24;
+const BUTTON_BORDER_THICKNESS: f32 = 2.0;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum MenuAction {
+    Play,
+    /// Two players.
+    Versus,
+    /// Against the computer.
+    VersusComputer,
+    /// Watch the computer play.
+    Demo,
+    Resume,
+    PlayAgain,
+    /// Same seed, racing the same ghost if there is one.
+    RetrySeed,
+    EnterSeed,
+    ExportRun,
+    ImportRun,
+    TitleMenu,
+}
+
+impl MenuAction {
+    /// Game over keys aren't steering keys, so a turn pressed as a versus round ends doesn't
+    /// trigger them.
+    pub(crate) fn key(self) -> mq::KeyCode {
+        match self {
+            Self::Play | Self::Resume | Self::PlayAgain => mq::KeyCode::Enter,
+            Self::Versus => mq::KeyCode::V,
+            Self::VersusComputer => mq::KeyCode::O,
+            Self::Demo => mq::KeyCode::D,
+            Self::RetrySeed => mq::KeyCode::R,
+            Self::EnterSeed => mq::KeyCode::N,
+            Self::ExportRun => mq::KeyCode::X,
+            Self::ImportRun => mq::KeyCode::I,
+            Self::TitleMenu => mq::KeyCode::M,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Play => "Play",
+            Self::Versus => "Versus",
+            Self::VersusComputer => "Versus computer",
+            Self::Demo => "Watch demo",
+            Self::Resume => "Resume",
+            Self::PlayAgain => "Play again",
+            Self::RetrySeed => "Retry seed",
+            Self::EnterSeed => "Enter a seed",
+            Self::ExportRun => "Export run",
+            Self::ImportRun => "Import run",
+            Self::TitleMenu => "Title menu",
+        }
+    }
+}
+
+/// A column of buttons.
+pub(crate) struct Menu {
+    buttons: Vec<(MenuAction, SimpleButton)>,
+}
+
+impl Menu {
+    pub(crate) fn new(actions: &[MenuAction]) -> Self {
+        Self {
+            buttons: actions
+                .iter()
+                .map(|action| (*action, SimpleButton::new(0.0, 0.0, 0.0, 0.0)))
+                .collect(),
+        }
+    }
+
+    /// Height of the whole column.
+    pub(crate) fn height(&self) -> f32 {
+        let num_buttons = self.buttons.len() as f32;
+        (num_buttons * (BUTTON_HEIGHT + BUTTON_GAP) - BUTTON_GAP).max(0.0)
+    }
+
+    /// Lay the buttons out from `(x, y)` down, and return the action that was clicked or had its
+    /// key pressed this frame, if any.
+    pub(crate) fn update(&mut self, x: f32, y: f32) -> Option<MenuAction> {
+        let mut opt_triggered = None;
+        for (i, (action, button)) in self.buttons.iter_mut().enumerate() {
+            button.set_bounds(
+                x,
+                y + i as f32 * (BUTTON_HEIGHT + BUTTON_GAP),
+                BUTTON_WIDTH,
+                BUTTON_HEIGHT,
+            );
+            // Tick every button, so none are left stuck pressed.
+            let clicked = button.tick_state().should_trigger_action();
+            if opt_triggered.is_none() && (clicked || mq::is_key_pressed(action.key())) {
+                opt_triggered = Some(*action);
+            }
+        }
+        opt_triggered
+    }
+
+    pub(crate) fn draw(&self) {
+        for (action, button) in &self.buttons {
+            let color = if button.is_pressed() {
+                mq::GRAY
+            } else {
+                mq::WHITE
+            };
+            button.draw(
+                color,
+                mq::DARKGRAY,
+                BUTTON_BORDER_THICKNESS,
+                format!("{} [{}]", action.label(), action.key().to_lowercase()),
+                BUTTON_FONT_SIZE,
+                mq::DARKGRAY,
+            );
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Snakes drawn as connected bodies that glide from cell to cell between movement ticks, with a
+//! head that looks where it's going.
+use crate::game::{Game, Snake};
+use crate::{Direction, Point};
+use better_quad::bq::Timestamp;
+use better_quad::mq;
+use std::time::Duration;
+
+/// Fraction of a cell, at the head. Segments taper towards the tail.
+const HEAD_RADIUS: f32 = 0.42;
+const BODY_RADIUS: f32 = 0.36;
+const TAIL_RADIUS: f32 = 0.22;
+/// How far towards white the body fades by the tail.
+const TAIL_FADE: f32 = 0.55;
+/// Further apart than this, in cells, segments are either side of a wrap around rather than
+/// neighbors.
+const MAX_JOINED_DISTANCE: f32 = 1.5;
+/// Fractions of a cell, from the center of the head.
+const EYE_FORWARD: f32 = 0.14;
+const EYE_SPREAD: f32 = 0.17;
+const EYE_RADIUS: f32 = 0.11;
+const PUPIL_RADIUS: f32 = 0.055;
+const PUPIL_FORWARD: f32 = 0.045;
+
+/// Where the board is on screen, fit to the window.
+pub(crate) struct BoardLayout {
+    pub(crate) offset_x: f32,
+    pub(crate) offset_y: f32,
+    /// Cell size.
+    pub(crate) sq_size: f32,
+}
+
+impl BoardLayout {
+    pub(crate) fn new(game: &Game) -> Self {
+        let rules = &game.rules;
+        // 50 = total padding (25 lrud)
+        let sq_size = ((mq::screen_width() - 50.) / rules.board_width as f32)
+            .min((mq::screen_height() - 50.) / rules.board_height as f32);
+        Self {
+            offset_x: (mq::screen_width() - sq_size * rules.board_width as f32) / 2.,
+            offset_y: (mq::screen_height() - sq_size * rules.board_height as f32) / 2.,
+            sq_size,
+        }
+    }
+
+    /// Top left corner of a cell.
+    pub(crate) fn cell_corner(&self, (x, y): Point) -> mq::Vec2 {
+        mq::vec2(
+            self.offset_x + x as f32 * self.sq_size,
+            self.offset_y + y as f32 * self.sq_size,
+        )
+    }
+
+    /// Center of a cell, or between cells for fractional positions.
+    pub(crate) fn cell_center(&self, position: mq::Vec2) -> mq::Vec2 {
+        mq::vec2(self.offset_x, self.offset_y) + (position + mq::Vec2::splat(0.5)) * self.sq_size
+    }
+}
+
+/// How far through the current movement tick period `now` is, from 0 (just ticked) to 1. Until
+/// the first tick since movement started or resumed, snakes stay put on their cells.
+pub(crate) fn tick_progress(
+    opt_last_tick: Option<Timestamp>,
+    period: Duration,
+    now: Timestamp,
+) -> f32 {
+    opt_last_tick.map_or(1.0, |last_tick| {
+        ((now - last_tick).as_secs_f32() / period.as_secs_f32()).min(1.0)
+    })
+}
+
+/// Draw `snake` `progress` of the way from its last cells to its current ones, fading from
+/// `body_color` at the head end towards the tail.
+pub(crate) fn draw_snake(
+    layout: &BoardLayout,
+    snake: &Snake,
+    progress: f32,
+    head_color: mq::Color,
+    body_color: mq::Color,
+) {
+    // Head first. Each segment slides from where the one ahead of it was.
+    let cells = std::iter::once(snake.head)
+        .chain(snake.body.iter().copied())
+        .collect::<Vec<_>>();
+    let prev_cells = cells.iter().skip(1).copied().chain([snake.prev_tail]);
+    let positions = cells
+        .iter()
+        .zip(prev_cells)
+        .map(|(&cell, prev_cell)| slide(prev_cell, cell, progress))
+        .collect::<Vec<_>>();
+
+    let tail_color = mix(body_color, mq::WHITE.with_alpha(body_color.a), TAIL_FADE);
+    let segment_style = |i: usize| {
+        let along = i as f32 / (positions.len() - 1).max(1) as f32;
+        (
+            mix(body_color, tail_color, along),
+            (BODY_RADIUS + (TAIL_RADIUS - BODY_RADIUS) * along) * layout.sq_size,
+        )
+    };
+
+    // From the tail, so segments nearer the head go on top.
+    for i in (1..positions.len()).rev() {
+        let (color, radius) = segment_style(i);
+        let center = layout.cell_center(positions[i]);
+        mq::draw_circle(center.x, center.y, radius, color);
+        // Joined to the segment ahead, unless they're on either side of a wrap around.
+        let ahead = positions[i - 1];
+        if positions[i].distance(ahead) < MAX_JOINED_DISTANCE {
+            let ahead = layout.cell_center(ahead);
+            mq::draw_line(center.x, center.y, ahead.x, ahead.y, radius * 2., color);
+        }
+    }
+
+    let head = layout.cell_center(positions[0]);
+    mq::draw_circle(head.x, head.y, HEAD_RADIUS * layout.sq_size, head_color);
+    draw_eyes(layout, head, snake.next_dir, head_color.a);
+}
+
+fn draw_eyes(layout: &BoardLayout, head: mq::Vec2, facing: Direction, alpha: f32) {
+    let forward = direction_vec(facing);
+    let side = forward.perp();
+    for spread in [-EYE_SPREAD, EYE_SPREAD] {
+        let eye = head + (forward * EYE_FORWARD + side * spread) * layout.sq_size;
+        mq::draw_circle(
+            eye.x,
+            eye.y,
+            EYE_RADIUS * layout.sq_size,
+            mq::WHITE.with_alpha(alpha),
+        );
+        let pupil = eye + forward * PUPIL_FORWARD * layout.sq_size;
+        mq::draw_circle(
+            pupil.x,
+            pupil.y,
+            PUPIL_RADIUS * layout.sq_size,
+            mq::BLACK.with_alpha(alpha),
+        );
+    }
+}
+
+/// `progress` of the way from `from` to `to`, in cells. Moves across a wrap around jump straight
+/// there rather than sliding back across the whole board.
+fn slide(from: Point, to: Point, progress: f32) -> mq::Vec2 {
+    let from = point_vec(from);
+    let to = point_vec(to);
+    if from.distance(to) >= MAX_JOINED_DISTANCE {
+        return to;
+    }
+    from.lerp(to, progress)
+}
+
+fn point_vec((x, y): Point) -> mq::Vec2 {
+    mq::vec2(x as f32, y as f32)
+}
+
+/// Screen space, so up is negative.
+fn direction_vec(dir: Direction) -> mq::Vec2 {
+    match dir {
+        Direction::Right => mq::vec2(1., 0.),
+        Direction::Left => mq::vec2(-1., 0.),
+        Direction::Up => mq::vec2(0., -1.),
+        Direction::Down => mq::vec2(0., 1.),
+    }
+}
+
+/// `amount` of the way from `from` to `to`.
+fn mix(from: mq::Color, to: mq::Color, amount: f32) -> mq::Color {
+    mq::Color::new(
+        from.r + (to.r - from.r) * amount,
+        from.g + (to.g - from.g) * amount,
+        from.b + (to.b - from.b) * amount,
+        from.a + (to.a - from.a) * amount,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{slide, tick_progress};
+    use better_quad::bq::Timestamp;
+    use better_quad::mq;
+    use std::time::Duration;
+
+    #[test]
+    fn test_slide() {
+        assert_eq!(mq::vec2(2.25, 3.), slide((2, 3), (3, 3), 0.25));
+        assert_eq!(mq::vec2(3., 3.), slide((2, 3), (3, 3), 1.));
+        // Across a wrap around.
+        assert_eq!(mq::vec2(0., 3.), slide((15, 3), (0, 3), 0.25));
+
+        let now = Timestamp::now();
+        let period = Duration::from_millis(200);
+        assert_eq!(1.0, tick_progress(None, period, now));
+        assert_eq!(0.0, tick_progress(Some(now), period, now));
+    }
+}
@@ -0,0 +1,411 @@
+//! Plain text record of a run, so it can leave the process (clipboard/file) and be played back as a
+//! ghost alongside a new game with the same seed.
+//!
+//! ```text
+//! caterpillar-run 1
+//! rules width=16 height=16 wrap=false length=1 fruits=1 start=0,0 players=2
+//! obstacles 4,4 4,5
+//! seed 1234
+//! turn tick=3 dir=down
+//! turn tick=3 player=2 dir=up
+//! turn tick=9 dir=right
+//! ```
+//!
+//! A turn applies on the movement tick after `tick` ticks were played, to player 1 unless another
+//! `player` is given. The obstacles line is left out when there aren't any. Blank lines and lines
+//! starting with `#` are ignored.
+use crate::game::Game;
+use crate::rules::SnakeRules;
+use crate::{Direction, Point};
+use better_quad::bq::Timestamp;
+use std::fmt::Write;
+
+const HEADER: &str = "caterpillar-run 1";
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct RunRecord {
+    pub(crate) rules: SnakeRules,
+    pub(crate) seed: u64,
+    /// In tick then snake order, at most one per snake per tick.
+    pub(crate) turns: Vec<Turn>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Turn {
+    pub(crate) tick: u64,
+    /// Index into `Game::snakes`.
+    pub(crate) snake: usize,
+    pub(crate) dir: Direction,
+}
+
+impl RunRecord {
+    pub(crate) fn new(rules: SnakeRules, seed: u64) -> Self {
+        Self {
+            rules,
+            seed,
+            turns: Vec::new(),
+        }
+    }
+
+    pub(crate) fn to_text(&self) -> String {
+        let rules = &self.rules;
+        let mut text = String::new();
+        writeln!(text, "{HEADER}").unwrap();
+        writeln!(
+            text,
+            "rules width={} height={} wrap={} length={} fruits={} start={} players={}",
+            rules.board_width,
+            rules.board_height,
+            rules.wrap_around,
+            rules.initial_length,
+            rules.num_fruits,
+            format_point(rules.start),
+            rules.num_players,
+        )
+        .unwrap();
+        if !rules.obstacles.is_empty() {
+            // Sorted so the same rules always write the same text.
+            let mut obstacles = rules.obstacles.iter().copied().collect::<Vec<_>>();
+            obstacles.sort_unstable_by_key(|&(x, y)| (y, x));
+            let obstacles = obstacles
+                .into_iter()
+                .map(format_point)
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(text, "obstacles {obstacles}").unwrap();
+        }
+        writeln!(text, "seed {}", self.seed).unwrap();
+        for turn in &self.turns {
+            let player = match turn.snake {
+                0 => String::new(),
+                snake => format!(" player={}", snake + 1),
+            };
+            writeln!(
+                text,
+                "turn tick={}{player} dir={}",
+                turn.tick,
+                format_dir(turn.dir)
+            )
+            .unwrap();
+        }
+
+        text
+    }
+
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        match lines.next() {
+            Some((_, HEADER)) => {}
+            Some((line_number, _)) => {
+                return Err(format!("line {line_number}: expected header '{HEADER}'"))
+            }
+            None => return Err("record is empty".to_string()),
+        }
+
+        let mut opt_rules = None;
+        let mut opt_seed = None;
+        let mut turns: Vec<Turn> = Vec::new();
+        for (line_number, line) in lines {
+            let with_line_number = |e: String| format!("line {line_number}: {e}");
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("rules") => {
+                    if opt_rules.is_some() {
+                        return Err(with_line_number("duplicate rules".to_string()));
+                    }
+                    opt_rules = Some(parse_rules(tokens).map_err(with_line_number)?);
+                }
+                Some("obstacles") => {
+                    let Some(rules) = &mut opt_rules else {
+                        return Err(with_line_number("obstacles before rules".to_string()));
+                    };
+                    for token in tokens {
+                        rules
+                            .obstacles
+                            .insert(parse_point(token).map_err(with_line_number)?);
+                    }
+                }
+                Some("seed") => {
+                    if opt_seed.is_some() {
+                        return Err(with_line_number("duplicate seed".to_string()));
+                    }
+                    let value = tokens.next().unwrap_or_default();
+                    let seed = value
+                        .parse()
+                        .map_err(|_| with_line_number(format!("'{value}' isn't a seed")))?;
+                    opt_seed = Some(seed);
+                }
+                Some("turn") => {
+                    let turn = parse_turn(tokens).map_err(with_line_number)?;
+                    if turns
+                        .last()
+                        .is_some_and(|last| (last.tick, last.snake) >= (turn.tick, turn.snake))
+                    {
+                        return Err(with_line_number(
+                            "turns must be in tick then player order".to_string(),
+                        ));
+                    }
+                    turns.push(turn);
+                }
+                Some(other) => return Err(with_line_number(format!("unknown entry '{other}'"))),
+                None => unreachable!("blank lines are filtered"),
+            }
+        }
+
+        let Some(rules) = opt_rules else {
+            return Err("record has no rules".to_string());
+        };
+        rules.validate()?;
+        if let Some(turn) = turns.iter().find(|turn| turn.snake >= rules.num_players) {
+            return Err(format!(
+                "turn for player {}, but there are only {}",
+                turn.snake + 1,
+                rules.num_players
+            ));
+        }
+        let Some(seed) = opt_seed else {
+            return Err("record has no seed".to_string());
+        };
+
+        Ok(Self { rules, seed, turns })
+    }
+}
+
+/// A recorded run played back at its own speed, alongside the live game.
+pub(crate) struct Ghost {
+    pub(crate) record: RunRecord,
+    pub(crate) game: Game,
+    next_turn_index: usize,
+    last_update: Timestamp,
+    /// When the last movement tick was played, since starting or resuming.
+    pub(crate) opt_last_tick: Option<Timestamp>,
+}
+
+impl Ghost {
+    pub(crate) fn new(record: RunRecord, now: Timestamp) -> Self {
+        Self {
+            game: Game::new(record.rules.clone(), record.seed),
+            record,
+            next_turn_index: 0,
+            last_update: now,
+            opt_last_tick: None,
+        }
+    }
+
+    /// The same run played back from the start.
+    pub(crate) fn restarted(&self, now: Timestamp) -> Self {
+        Self::new(self.record.clone(), now)
+    }
+
+    /// Carry on from `now` after a pause, rather than catching up.
+    pub(crate) fn resume(&mut self, now: Timestamp) {
+        self.last_update = now;
+        self.opt_last_tick = None;
+    }
+
+    pub(crate) fn update(&mut self, now: Timestamp) {
+        // Once it's over it stays where it ended, rather than sliding through its last move again.
+        if self.game.game_over {
+            return;
+        }
+        if now - self.last_update > self.game.movement_tick_speed {
+            self.last_update = now;
+            self.opt_last_tick = Some(now);
+            self.tick();
+        }
+    }
+
+    fn tick(&mut self) {
+        let mut turns = vec![None; self.game.snakes.len()];
+        while let Some(turn) = self
+            .record
+            .turns
+            .get(self.next_turn_index)
+            .filter(|turn| turn.tick == self.game.num_ticks)
+        {
+            turns[turn.snake] = Some(turn.dir);
+            self.next_turn_index += 1;
+        }
+        self.game.tick(&turns);
+    }
+}
+
+/// Iterate `key=value` tokens.
+fn key_values<'a>(
+    tokens: impl Iterator<Item = &'a str>,
+) -> impl Iterator<Item = Result<(&'a str, &'a str), String>> {
+    tokens.map(|token| {
+        token
+            .split_once('=')
+            .ok_or_else(|| format!("expected 'key=value', got '{token}'"))
+    })
+}
+
+fn parse_rules<'a>(tokens: impl Iterator<Item = &'a str>) -> Result<SnakeRules, String> {
+    let mut rules = SnakeRules::default();
+    for key_value in key_values(tokens) {
+        let (key, value) = key_value?;
+        let invalid = || format!("invalid {key} '{value}'");
+        match key {
+            "width" => rules.board_width = value.parse().map_err(|_| invalid())?,
+            "height" => rules.board_height = value.parse().map_err(|_| invalid())?,
+            "wrap" => rules.wrap_around = value.parse().map_err(|_| invalid())?,
+            "length" => rules.initial_length = value.parse().map_err(|_| invalid())?,
+            "fruits" => rules.num_fruits = value.parse().map_err(|_| invalid())?,
+            "start" => rules.start = parse_point(value)?,
+            "players" => rules.num_players = value.parse().map_err(|_| invalid())?,
+            _ => return Err(format!("unknown rule '{key}'")),
+        }
+    }
+    Ok(rules)
+}
+
+fn parse_turn<'a>(tokens: impl Iterator<Item = &'a str>) -> Result<Turn, String> {
+    let mut opt_tick = None;
+    let mut snake = 0;
+    let mut opt_dir = None;
+    for key_value in key_values(tokens) {
+        match key_value? {
+            ("tick", value) => {
+                opt_tick = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("'{value}' isn't a tick"))?,
+                )
+            }
+            ("player", value) => {
+                snake = value
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|player| player.checked_sub(1))
+                    .ok_or_else(|| format!("'{value}' isn't a player"))?
+            }
+            ("dir", value) => opt_dir = Some(parse_dir(value)?),
+            (key, _) => return Err(format!("unknown turn field '{key}'")),
+        }
+    }
+    match (opt_tick, opt_dir) {
+        (Some(tick), Some(dir)) => Ok(Turn { tick, snake, dir }),
+        _ => Err("turn needs a tick and dir".to_string()),
+    }
+}
+
+fn format_point((x, y): Point) -> String {
+    format!("{x},{y}")
+}
+
+fn parse_point(text: &str) -> Result<Point, String> {
+    text.split_once(',')
+        .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+        .ok_or_else(|| format!("expected 'x,y', got '{text}'"))
+}
+
+fn format_dir(dir: Direction) -> &'static str {
+    match dir {
+        Direction::Right => "right",
+        Direction::Left => "left",
+        Direction::Up => "up",
+        Direction::Down => "down",
+    }
+}
+
+fn parse_dir(text: &str) -> Result<Direction, String> {
+    match text {
+        "right" => Ok(Direction::Right),
+        "left" => Ok(Direction::Left),
+        "up" => Ok(Direction::Up),
+        "down" => Ok(Direction::Down),
+        _ => Err(format!("unknown direction '{text}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ghost, RunRecord, Turn};
+    use crate::game::Game;
+    use crate::render::tick_progress;
+    use crate::{Direction, SnakeRules};
+    use better_quad::bq::Timestamp;
+
+    #[test]
+    fn test_record_round_trip_and_replay() {
+        let rules = SnakeRules::from_level("........\n.S..#...\n........\n")
+            .unwrap()
+            .with_wrap_around(true)
+            .with_num_fruits(2)
+            .with_num_players(2);
+        let mut record = RunRecord::new(rules.clone(), 1234);
+        let mut game = Game::new(rules, record.seed);
+        let turns = [
+            [Some(Direction::Down), Some(Direction::Up)],
+            [None, None],
+            [Some(Direction::Left), Some(Direction::Left)],
+            [None, None],
+            [None, None],
+            [Some(Direction::Up), None],
+        ];
+        for tick_turns in turns {
+            for (snake, opt_dir) in tick_turns.iter().enumerate() {
+                if let Some(dir) = *opt_dir {
+                    let tick = game.num_ticks;
+                    record.turns.push(Turn { tick, snake, dir });
+                }
+            }
+            game.tick(&tick_turns);
+        }
+
+        let parsed = RunRecord::parse(&record.to_text()).unwrap();
+        assert_eq!(record, parsed);
+
+        let mut ghost = Ghost::new(parsed, Timestamp::now());
+        for _ in turns {
+            ghost.tick();
+        }
+        for (snake, ghost_snake) in game.snakes.iter().zip(&ghost.game.snakes) {
+            assert_eq!(snake.head, ghost_snake.head);
+            assert_eq!(snake.body, ghost_snake.body);
+        }
+        assert_eq!(
+            game.fruits.iter().map(|f| f.point).collect::<Vec<_>>(),
+            ghost
+                .game
+                .fruits
+                .iter()
+                .map(|f| f.point)
+                .collect::<Vec<_>>()
+        );
+
+        assert!(RunRecord::parse("").is_err());
+        assert!(RunRecord::parse("caterpillar-run 1\nseed 1\n").is_err());
+        assert!(RunRecord::parse("caterpillar-run 1\nrules width=0\nseed 1\n").is_err());
+        assert!(RunRecord::parse(
+            "caterpillar-run 1\nrules\nseed 1\nturn tick=0 player=2 dir=up\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_finished_ghost_stays_put() {
+        // Over within a couple of ticks on a board this small.
+        let rules = SnakeRules::from_level("S.\n")
+            .unwrap()
+            .with_initial_length(1);
+        let mut now = Timestamp::now();
+        let mut ghost = Ghost::new(RunRecord::new(rules, 1), now);
+        for _ in 0..2 {
+            now = now + ghost.game.movement_tick_speed * 2;
+            ghost.update(now);
+        }
+        assert!(ghost.game.game_over);
+
+        now = now + ghost.game.movement_tick_speed * 2;
+        ghost.update(now);
+        let period = ghost.game.movement_tick_speed;
+        assert_eq!(1.0, tick_progress(ghost.opt_last_tick, period, now));
+    }
+}
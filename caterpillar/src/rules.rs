@@ -0,0 +1,276 @@
+//! Board and snake configuration: size, walls, obstacles, and how long the snake starts.
+use crate::{Direction, Point};
+use std::collections::HashSet;
+
+const DEFAULT_BOARD_SIZE: i16 = 16;
+/// Keeps the cells big enough to see.
+const MAX_BOARD_SIZE: i16 = 100;
+/// One per set of direction keys.
+pub(crate) const MAX_PLAYERS: usize = 2;
+
+// Level format chars.
+const LEVEL_OBSTACLE: char = '#';
+const LEVEL_FREE: char = '.';
+const LEVEL_START: char = 'S';
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnakeRules {
+    pub board_width: i16,
+    pub board_height: i16,
+    /// Leaving one side of the board comes back in on the other instead of hitting the wall.
+    pub wrap_around: bool,
+    pub obstacles: HashSet<Point>,
+    /// Where the first snake's head starts, heading right. A second snake starts opposite, mirrored
+    /// through the center of the board and heading left.
+    pub start: Point,
+    /// Including the head. The body starts coiled up under the head, and uncoils as it moves.
+    pub initial_length: usize,
+    /// Regular fruits on the board at once. Special fruits come on top of these.
+    pub num_fruits: usize,
+    /// Snakes on the board, each with a player. More than one plays versus rounds.
+    pub num_players: usize,
+}
+
+impl Default for SnakeRules {
+    fn default() -> Self {
+        Self {
+            board_width: DEFAULT_BOARD_SIZE,
+            board_height: DEFAULT_BOARD_SIZE,
+            wrap_around: false,
+            obstacles: HashSet::new(),
+            start: (0, 0),
+            initial_length: 1,
+            num_fruits: 1,
+            num_players: 1,
+        }
+    }
+}
+
+impl SnakeRules {
+    /// Board from ASCII art, a row per line: `#` is an obstacle, `.` (or a space) is free, and `S`
+    /// is where the snake starts (the top left if there's none). The widest row sets the board
+    /// width, with shorter rows padded with free cells. For example:
+    ///
+    /// ```text
+    /// ..........
+    /// .S........
+    /// ....##....
+    /// ....##....
+    /// ..........
+    /// ```
+    pub fn from_level(level: &str) -> Result<Self, String> {
+        let rows = level.trim_end().lines().collect::<Vec<_>>();
+        let board_height = rows.len();
+        let board_width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        if board_width == 0 || board_height == 0 {
+            return Err("level is empty".to_string());
+        }
+        if board_width > MAX_BOARD_SIZE as usize || board_height > MAX_BOARD_SIZE as usize {
+            return Err(format!(
+                "level is {board_width}x{board_height}, \
+                over the max of {MAX_BOARD_SIZE}x{MAX_BOARD_SIZE}"
+            ));
+        }
+
+        let mut rules = Self {
+            board_width: board_width as i16,
+            board_height: board_height as i16,
+            ..Self::default()
+        };
+        let mut opt_start = None;
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let point = (x as i16, y as i16);
+                match c {
+                    LEVEL_OBSTACLE => {
+                        rules.obstacles.insert(point);
+                    }
+                    LEVEL_FREE | ' ' => {}
+                    LEVEL_START if opt_start.is_none() => opt_start = Some(point),
+                    LEVEL_START => return Err("level has more than one start".to_string()),
+                    _ => {
+                        return Err(format!(
+                            "unknown level char '{c}' at row {}, column {}",
+                            y + 1,
+                            x + 1
+                        ))
+                    }
+                }
+            }
+        }
+        rules.start = opt_start.unwrap_or((0, 0));
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    pub fn with_wrap_around(mut self, wrap_around: bool) -> Self {
+        self.wrap_around = wrap_around;
+        self
+    }
+
+    pub fn with_initial_length(mut self, initial_length: usize) -> Self {
+        self.initial_length = initial_length;
+        self
+    }
+
+    pub fn with_num_fruits(mut self, num_fruits: usize) -> Self {
+        self.num_fruits = num_fruits;
+        self
+    }
+
+    pub fn with_num_players(mut self, num_players: usize) -> Self {
+        self.num_players = num_players;
+        self
+    }
+
+    /// Where each player's snake starts, and the direction it's heading.
+    pub(crate) fn starts(&self) -> Vec<(Point, Direction)> {
+        let mirrored_start = (
+            self.board_width - 1 - self.start.0,
+            self.board_height - 1 - self.start.1,
+        );
+        [
+            (self.start, Direction::Right),
+            (mirrored_start, Direction::Left),
+        ]
+        .into_iter()
+        .take(self.num_players)
+        .collect()
+    }
+
+    /// Check the rules make a playable game.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_BOARD_SIZE).contains(&self.board_width)
+            || !(1..=MAX_BOARD_SIZE).contains(&self.board_height)
+        {
+            return Err(format!(
+                "board size must be 1 to {MAX_BOARD_SIZE}, not {}x{}",
+                self.board_width, self.board_height
+            ));
+        }
+        // The snake starts coiled up on its start cell, but it's allocated at full length.
+        let num_free_cells = self.board_width as usize * self.board_height as usize
+            - self
+                .obstacles
+                .iter()
+                .filter(|obstacle| self.is_on_board(**obstacle))
+                .count();
+        if !(1..=num_free_cells).contains(&self.initial_length) {
+            return Err(format!(
+                "initial length must be 1 to {num_free_cells} (the free cells), not {}",
+                self.initial_length
+            ));
+        }
+        if self.num_fruits == 0 {
+            return Err("there must be at least 1 fruit".to_string());
+        }
+        if !(1..=MAX_PLAYERS).contains(&self.num_players) {
+            return Err(format!(
+                "there must be 1 to {MAX_PLAYERS} players, not {}",
+                self.num_players
+            ));
+        }
+        let starts = self.starts();
+        for (i, (start, _)) in starts.iter().enumerate() {
+            if !self.is_on_board(*start) || self.obstacles.contains(start) {
+                return Err(format!(
+                    "start {start:?} of player {} must be a free cell on the board",
+                    i + 1
+                ));
+            }
+            if starts[..i].iter().any(|(other, _)| other == start) {
+                return Err(format!(
+                    "players can't share the start {start:?}, move it off the center"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn is_on_board(&self, point: Point) -> bool {
+        (0..self.board_width).contains(&point.0) && (0..self.board_height).contains(&point.1)
+    }
+
+    /// Where the snake ends up after moving to `point`, or `None` if it hit a wall.
+    pub(crate) fn resolve_move(&self, point: Point) -> Option<Point> {
+        if self.wrap_around {
+            Some((
+                point.0.rem_euclid(self.board_width),
+                point.1.rem_euclid(self.board_height),
+            ))
+        } else {
+            self.is_on_board(point).then_some(point)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnakeRules;
+    use crate::Direction;
+
+    #[test]
+    fn test_from_level() {
+        let rules = SnakeRules::from_level("....\n.S#\n..##\n").unwrap();
+        assert_eq!((4, 3), (rules.board_width, rules.board_height));
+        assert_eq!((1, 1), rules.start);
+        let mut obstacles = rules.obstacles.iter().copied().collect::<Vec<_>>();
+        obstacles.sort_unstable();
+        assert_eq!(vec![(2, 1), (2, 2), (3, 2)], obstacles);
+
+        assert!(SnakeRules::from_level("").is_err());
+        assert!(SnakeRules::from_level("S.S").is_err());
+        assert!(SnakeRules::from_level("..x").is_err());
+
+        for level in [
+            include_str!("../levels/pillars.txt"),
+            include_str!("../levels/corridors.txt"),
+        ] {
+            assert!(SnakeRules::from_level(level).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_resolve_move() {
+        let walled = SnakeRules::default();
+        assert_eq!(Some((3, 0)), walled.resolve_move((3, 0)));
+        assert_eq!(None, walled.resolve_move((-1, 0)));
+        assert_eq!(None, walled.resolve_move((0, 16)));
+
+        let wrapped = walled.with_wrap_around(true);
+        assert_eq!(Some((15, 0)), wrapped.resolve_move((-1, 0)));
+        assert_eq!(Some((0, 0)), wrapped.resolve_move((0, 16)));
+    }
+
+    #[test]
+    fn test_initial_length_fits_board() {
+        let rules = SnakeRules::from_level("S.\n.#\n").unwrap();
+        assert!(rules.clone().with_initial_length(3).validate().is_ok());
+        assert!(rules.clone().with_initial_length(4).validate().is_err());
+        assert!(rules.with_initial_length(4_000_000_000).validate().is_err());
+    }
+
+    #[test]
+    fn test_versus_starts() {
+        let versus = SnakeRules::from_level("S..\n...\n..#\n")
+            .unwrap()
+            .with_num_players(2);
+        // The mirrored start is an obstacle.
+        assert!(versus.validate().is_err());
+
+        let versus = SnakeRules::default().with_num_players(2);
+        assert_eq!(
+            vec![((0, 0), Direction::Right), ((15, 15), Direction::Left)],
+            versus.starts()
+        );
+        assert!(SnakeRules::from_level("...\n.S.\n...\n")
+            .unwrap()
+            .with_num_players(2)
+            .validate()
+            .is_err());
+    }
+}
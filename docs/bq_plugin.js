@@ -0,0 +1,50 @@
+// Browser side of better-quad's WASM imports. Must be loaded after mq_js_bundle.js and before load().
+"use strict";
+
+function bq_read_str(ptr, len) {
+    return new TextDecoder().decode(new Uint8Array(wasm_memory.buffer, ptr, len));
+}
+
+miniquad_add_plugin({
+    name: "better_quad",
+    version: 1,
+    register_plugin: function (importObject) {
+        // bq_storage.rs
+        importObject.env.bq_storage_get_len = function (key_ptr, key_len) {
+            const value = window.localStorage.getItem(bq_read_str(key_ptr, key_len));
+            return value === null ? -1 : new TextEncoder().encode(value).length;
+        };
+        importObject.env.bq_storage_get = function (key_ptr, key_len, buf_ptr) {
+            const value = window.localStorage.getItem(bq_read_str(key_ptr, key_len)) || "";
+            const bytes = new TextEncoder().encode(value);
+            new Uint8Array(wasm_memory.buffer, buf_ptr, bytes.length).set(bytes);
+        };
+        importObject.env.bq_storage_set = function (key_ptr, key_len, value_ptr, value_len) {
+            window.localStorage.setItem(bq_read_str(key_ptr, key_len), bq_read_str(value_ptr, value_len));
+        };
+        importObject.env.bq_storage_remove = function (key_ptr, key_len) {
+            window.localStorage.removeItem(bq_read_str(key_ptr, key_len));
+        };
+
+        // bq_export.rs
+        importObject.env.bq_export_download = function (name_ptr, name_len, contents_ptr, contents_len) {
+            const blob = new Blob([bq_read_str(contents_ptr, contents_len)], { type: "text/plain" });
+            const url = URL.createObjectURL(blob);
+            const link = document.createElement("a");
+            link.href = url;
+            link.download = bq_read_str(name_ptr, name_len);
+            link.click();
+            // Revoking right away can cancel the download in some browsers.
+            setTimeout(function () { URL.revokeObjectURL(url); }, 0);
+        };
+
+        // bq_url.rs
+        importObject.env.bq_url_query_len = function () {
+            return new TextEncoder().encode(window.location.search).length;
+        };
+        importObject.env.bq_url_query = function (buf_ptr) {
+            const bytes = new TextEncoder().encode(window.location.search);
+            new Uint8Array(wasm_memory.buffer, buf_ptr, bytes.length).set(bytes);
+        };
+    },
+});
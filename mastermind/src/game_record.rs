@@ -0,0 +1,359 @@
+//! Plain text record of a game, so a game can leave the process (clipboard/file) and be loaded back
+//! into the replay viewer.
+//!
+//! ```text
+//! mastermind-record 1
+//! rules slots=4 guesses=8 palette=red,orange,yellow,green,blue,purple
+//! password random seed=1234
+//! guess elapsed_ms=5120 colors=red,orange,yellow,green correct=1 misplaced=2
+//! guess elapsed_ms=9870 colors=red,red,blue,green correct=4 misplaced=0
+//! ```
+//!
+//! A player specified password is written as `password player colors=red,orange,yellow,green`.
+//! Blank lines and lines starting with `#` are ignored.
+use crate::password::{Password, PasswordSource};
+use crate::{evaluate_guess, Color, SubmittedGuess, COLOR_PALETTE, NUM_GUESSES, NUM_SLOTS_PER_ROW};
+use std::fmt::Write;
+use std::time::Duration;
+
+const HEADER: &str = "mastermind-record 1";
+
+pub(crate) struct GameRecord {
+    pub(crate) password: Password,
+    // head: first guess; tail: most recent guess
+    pub(crate) guesses: Vec<SubmittedGuess>,
+}
+
+impl GameRecord {
+    pub(crate) fn new(password: Password, history: &[SubmittedGuess]) -> Self {
+        Self {
+            password,
+            guesses: history.to_vec(),
+        }
+    }
+
+    pub(crate) fn to_text(&self) -> String {
+        let mut text = String::new();
+        writeln!(text, "{HEADER}").unwrap();
+        writeln!(
+            text,
+            "rules slots={NUM_SLOTS_PER_ROW} guesses={NUM_GUESSES} palette={}",
+            format_colors(&COLOR_PALETTE)
+        )
+        .unwrap();
+        match self.password.source() {
+            PasswordSource::Random { seed } => writeln!(text, "password random seed={seed}"),
+            PasswordSource::Player => writeln!(
+                text,
+                "password player colors={}",
+                format_colors(self.password.password())
+            ),
+        }
+        .unwrap();
+        for submitted_guess in &self.guesses {
+            writeln!(
+                text,
+                "guess elapsed_ms={} colors={} correct={} misplaced={}",
+                submitted_guess.elapsed.as_millis(),
+                format_colors(&submitted_guess.row.guess),
+                submitted_guess.row.num_correct_hits,
+                submitted_guess.row.num_misplaced_hits,
+            )
+            .unwrap();
+        }
+
+        text
+    }
+
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        match lines.next() {
+            Some((_, HEADER)) => {}
+            Some((line_number, _)) => {
+                return Err(format!("line {line_number}: expected header '{HEADER}'"))
+            }
+            None => return Err("record is empty".to_string()),
+        }
+
+        let mut opt_password = None;
+        let mut guesses: Vec<SubmittedGuess> = Vec::new();
+        for (line_number, line) in lines {
+            let with_line_number = |e: String| format!("line {line_number}: {e}");
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("rules") => parse_rules(tokens).map_err(with_line_number)?,
+                Some("password") => {
+                    if opt_password.is_some() {
+                        return Err(with_line_number("duplicate password".to_string()));
+                    }
+                    opt_password = Some(parse_password(tokens).map_err(with_line_number)?);
+                }
+                Some("guess") => {
+                    let Some(password) = opt_password else {
+                        return Err(with_line_number("guess before password".to_string()));
+                    };
+                    if guesses.len() == NUM_GUESSES {
+                        return Err(with_line_number(format!("more than {NUM_GUESSES} guesses")));
+                    }
+                    if guesses
+                        .last()
+                        .is_some_and(|g| g.row.num_correct_hits == NUM_SLOTS_PER_ROW)
+                    {
+                        return Err(with_line_number("guess after winning guess".to_string()));
+                    }
+                    let submitted_guess =
+                        parse_guess(tokens, &password).map_err(with_line_number)?;
+                    if guesses
+                        .last()
+                        .is_some_and(|g| g.elapsed > submitted_guess.elapsed)
+                    {
+                        return Err(with_line_number("elapsed time went backwards".to_string()));
+                    }
+                    guesses.push(submitted_guess);
+                }
+                Some(other) => return Err(with_line_number(format!("unknown entry '{other}'"))),
+                None => unreachable!("blank lines are filtered"),
+            }
+        }
+
+        let Some(password) = opt_password else {
+            return Err("record has no password".to_string());
+        };
+
+        Ok(Self { password, guesses })
+    }
+}
+
+/// Iterate `key=value` tokens.
+fn key_values<'a>(
+    tokens: impl Iterator<Item = &'a str>,
+) -> impl Iterator<Item = Result<(&'a str, &'a str), String>> {
+    tokens.map(|token| {
+        token
+            .split_once('=')
+            .ok_or_else(|| format!("expected 'key=value', got '{token}'"))
+    })
+}
+
+fn parse_rules<'a>(tokens: impl Iterator<Item = &'a str>) -> Result<(), String> {
+    for key_value in key_values(tokens) {
+        let (key, value) = key_value?;
+        let matches_current_rules = match key {
+            "slots" => value == NUM_SLOTS_PER_ROW.to_string(),
+            "guesses" => value == NUM_GUESSES.to_string(),
+            "palette" => parse_colors(value)? == COLOR_PALETTE,
+            _ => return Err(format!("unknown rule '{key}'")),
+        };
+        if !matches_current_rules {
+            return Err(format!("unsupported rule {key}={value}"));
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_password<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Result<Password, String> {
+    let source = tokens.next();
+    let (key, value) = match key_values(tokens).next() {
+        Some(key_value) => key_value?,
+        None => return Err("password is missing its value".to_string()),
+    };
+
+    match (source, key) {
+        (Some("random"), "seed") => {
+            let seed = value
+                .parse()
+                .map_err(|_| format!("invalid seed '{value}'"))?;
+            Ok(Password::from_seed(seed))
+        }
+        (Some("player"), "colors") => Ok(Password::player_specified(parse_slot_colors(value)?)),
+        _ => Err("expected 'random seed=<n>' or 'player colors=<colors>'".to_string()),
+    }
+}
+
+fn parse_guess<'a>(
+    tokens: impl Iterator<Item = &'a str>,
+    password: &Password,
+) -> Result<SubmittedGuess, String> {
+    let mut opt_elapsed = None;
+    let mut opt_guess = None;
+    let mut opt_correct = None;
+    let mut opt_misplaced = None;
+    for key_value in key_values(tokens) {
+        let (key, value) = key_value?;
+        let parse_usize = || {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("invalid {key} '{value}'"))
+        };
+        match key {
+            "elapsed_ms" => {
+                let millis = value
+                    .parse()
+                    .map_err(|_| format!("invalid elapsed_ms '{value}'"))?;
+                opt_elapsed = Some(Duration::from_millis(millis));
+            }
+            "colors" => opt_guess = Some(parse_slot_colors(value)?),
+            "correct" => opt_correct = Some(parse_usize()?),
+            "misplaced" => opt_misplaced = Some(parse_usize()?),
+            _ => return Err(format!("unknown guess field '{key}'")),
+        }
+    }
+
+    let (Some(elapsed), Some(guess)) = (opt_elapsed, opt_guess) else {
+        return Err("guess requires elapsed_ms and colors".to_string());
+    };
+
+    // Feedback is derivable from the password, but is written out for human readers. Make sure the
+    // two agree so a hand edited record can't replay a different game than it claims.
+    let row = evaluate_guess(guess, *password.password());
+    if opt_correct.is_some_and(|c| c != row.num_correct_hits)
+        || opt_misplaced.is_some_and(|m| m != row.num_misplaced_hits)
+    {
+        return Err("feedback doesn't match password".to_string());
+    }
+
+    Ok(SubmittedGuess { row, elapsed })
+}
+
+fn parse_slot_colors(value: &str) -> Result<[Color; NUM_SLOTS_PER_ROW], String> {
+    let colors = parse_colors(value)?;
+    let num_colors = colors.len();
+    colors
+        .try_into()
+        .map_err(|_| format!("expected {NUM_SLOTS_PER_ROW} colors, got {num_colors}"))
+}
+
+fn parse_colors(value: &str) -> Result<Vec<Color>, String> {
+    value
+        .split(',')
+        .map(|name| Color::from_name(name).ok_or_else(|| format!("unknown color '{name}'")))
+        .collect()
+}
+
+fn format_colors(colors: &[Color]) -> String {
+    colors
+        .iter()
+        .map(|c| c.name())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameRecord;
+    use crate::password::{Password, PasswordSource};
+    use crate::{evaluate_guess, Color, SubmittedGuess};
+    use std::time::Duration;
+
+    fn submit(password: &Password, guess: [Color; 4], elapsed_ms: u64) -> SubmittedGuess {
+        SubmittedGuess {
+            row: evaluate_guess(guess, *password.password()),
+            elapsed: Duration::from_millis(elapsed_ms),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_player_password() {
+        let password =
+            Password::player_specified([Color::Red, Color::Orange, Color::Yellow, Color::Green]);
+        let history = [
+            submit(
+                &password,
+                [Color::Green, Color::Red, Color::Blue, Color::Blue],
+                4_250,
+            ),
+            submit(
+                &password,
+                [Color::Red, Color::Orange, Color::Yellow, Color::Green],
+                12_003,
+            ),
+        ];
+
+        let text = GameRecord::new(password, &history).to_text();
+        let parsed = GameRecord::parse(&text).unwrap();
+
+        assert_eq!(password.password(), parsed.password.password());
+        assert!(matches!(parsed.password.source(), PasswordSource::Player));
+        assert_eq!(history.len(), parsed.guesses.len());
+        for (expected, actual) in history.iter().zip(&parsed.guesses) {
+            assert_eq!(expected.row.guess, actual.row.guess);
+            assert_eq!(expected.row.num_correct_hits, actual.row.num_correct_hits);
+            assert_eq!(
+                expected.row.num_misplaced_hits,
+                actual.row.num_misplaced_hits
+            );
+            assert_eq!(expected.elapsed, actual.elapsed);
+        }
+    }
+
+    #[test]
+    fn test_seeded_password_is_derived_from_seed() {
+        let text = "mastermind-record 1\npassword random seed=42\n";
+
+        let first = GameRecord::parse(text).unwrap();
+        let second = GameRecord::parse(text).unwrap();
+
+        assert_eq!(first.password.password(), second.password.password());
+        assert!(matches!(
+            first.password.source(),
+            PasswordSource::Random { seed: 42 }
+        ));
+        assert_eq!(text, first.to_text().replace(RULES_LINE, ""));
+    }
+
+    const RULES_LINE: &str =
+        "rules slots=4 guesses=8 palette=red,orange,yellow,green,blue,purple\n";
+
+    #[test]
+    fn test_parse_errors() {
+        let test_cases = [
+            // (input, expected error)
+            ("", "record is empty"),
+            ("chess-record 1", "line 1: expected header"),
+            ("mastermind-record 1\n", "record has no password"),
+            (
+                "mastermind-record 1\nrules slots=5",
+                "line 2: unsupported rule slots=5",
+            ),
+            (
+                "mastermind-record 1\nguess elapsed_ms=1 colors=red,red,red,red",
+                "line 2: guess before password",
+            ),
+            (
+                "mastermind-record 1\npassword player colors=red,red,red",
+                "line 2: expected 4 colors, got 3",
+            ),
+            (
+                "mastermind-record 1\npassword player colors=red,red,red,teal",
+                "line 2: unknown color 'teal'",
+            ),
+            (
+                "mastermind-record 1\n\
+                password player colors=red,red,blue,blue\n\
+                guess elapsed_ms=1 colors=red,blue,red,blue correct=2 misplaced=0",
+                "line 3: feedback doesn't match password",
+            ),
+            (
+                "mastermind-record 1\n\
+                password player colors=red,red,blue,blue\n\
+                guess elapsed_ms=5 colors=red,red,red,red\n\
+                guess elapsed_ms=4 colors=blue,blue,blue,blue",
+                "line 4: elapsed time went backwards",
+            ),
+        ];
+
+        for (input, expected_error) in test_cases {
+            let actual_error = GameRecord::parse(input).err().unwrap();
+            assert!(
+                actual_error.starts_with(expected_error),
+                "Input: {input:?}, error: {actual_error:?}"
+            );
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::game_record::GameRecord;
 use crate::password::{Password, PasswordSource};
 use crate::victory_mouse_animation::VictoryMouseAnimations;
 use better_quad::bq::{BetterKeyCode, SimpleButton, TextAlignment, TextAnchorPoint};
@@ -11,6 +12,7 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::time::Duration;
 
+mod game_record;
 mod victory_mouse_animation;
 
 // Control consts
@@ -20,6 +22,11 @@ const KEY_NEW_PASSWORD: mq::KeyCode = mq::KeyCode::Space;
 const KEY_TOGGLE_NUMBER_OVERLAY: mq::KeyCode = mq::KeyCode::N;
 const KEY_PLAYER_EDIT_PASSWORD: mq::KeyCode = mq::KeyCode::P;
 const KEY_COPY_SEED: mq::KeyCode = mq::KeyCode::S;
+const KEY_EXPORT_RECORD: mq::KeyCode = mq::KeyCode::E;
+const KEY_IMPORT_RECORD: mq::KeyCode = mq::KeyCode::I;
+const KEY_REPLAY_STEP_BACKWARD: mq::KeyCode = mq::KeyCode::Left;
+const KEY_REPLAY_STEP_FORWARD: mq::KeyCode = mq::KeyCode::Right;
+const KEY_REPLAY_EXIT: mq::KeyCode = mq::KeyCode::Escape;
 
 // Game logic consts
 const COLOR_PALETTE: [Color; 6] = [
@@ -75,6 +82,11 @@ const SEED_TEXT_PADDING: f32 = 3.0;
 const VICTORY_MULTI_CURSOR_OFFSET: f32 = CURSOR_SIZE;
 const VICTORY_CURSOR_TITLE_FONT_SIZE: u16 = 50;
 const VICTORY_CURSOR_TITLE_OFFSET: f32 = 12.0;
+const STATUS_FONT_SIZE: u16 = 22;
+const STATUS_DISPLAY_DURATION: Duration = Duration::from_secs(4);
+/// Where exported records are written on native. WASM only has the clipboard.
+#[cfg(not(target_arch = "wasm32"))]
+const RECORD_EXPORT_FILE: &str = "mastermind-record.txt";
 
 struct BoardSizeDerivedConsts {
     row_width_guess: f32,
@@ -117,12 +129,14 @@ pub struct MastermindGame {
     state: GameState,
     password: Password,
     // head: first guess; tail: most recent guess
-    history: Vec<CompleteRow>,
+    history: Vec<SubmittedGuess>,
     mouse_color: Color,
     // Work around annoying (0, 0) initialization issue with mq.
     mouse_moved: bool,
     number_overlay: NumberOverlay,
     fps_counter: FpsCounter,
+    // Feedback for import/export, and when it was set.
+    status_text: Option<(String, Timestamp)>,
 }
 
 enum GameState {
@@ -141,6 +155,11 @@ enum GameState {
         mouse_animations: Box<VictoryMouseAnimations>,
     },
     TooManyGuesses,
+    Replay {
+        record: Box<GameRecord>,
+        // Number of guesses from the record currently shown on the board.
+        step: usize,
+    },
 }
 
 /// Separate mod to enforce RNG state and immutability.
@@ -171,6 +190,15 @@ mod password {
             }
         }
 
+        /// Reproduce a random password from its seed, e.g. when loading a game record.
+        pub(super) fn from_seed(seed: u64) -> Self {
+            bq::set_rand_seed(seed);
+            Self {
+                password: Color::random_array(&COLOR_PALETTE),
+                source: PasswordSource::Random { seed },
+            }
+        }
+
         pub(super) fn player_specified(password: [Color; NUM_SLOTS_PER_ROW]) -> Self {
             Self {
                 password,
@@ -257,6 +285,7 @@ impl MastermindGame {
             mouse_moved: false,
             number_overlay: NumberOverlay::Off,
             fps_counter: FpsCounter::new(),
+            status_text: None,
         }
     }
 
@@ -289,9 +318,72 @@ impl MastermindGame {
             mq::miniquad::window::clipboard_set(&format!("{}", bq::get_last_set_rand_seed()));
         }
 
+        if mq::is_key_pressed(KEY_EXPORT_RECORD) {
+            self.export_record(now);
+        }
+
+        if mq::is_key_pressed(KEY_IMPORT_RECORD) {
+            match mq::miniquad::window::clipboard_get() {
+                Some(text) => self.import_record(&text, now),
+                None => self.set_status_text("Clipboard is empty", now),
+            }
+        }
+        // Dropping a record file onto the window also loads it
+        for dropped_file in mq::get_dropped_files() {
+            match dropped_file.bytes.map(String::from_utf8) {
+                Some(Ok(text)) => self.import_record(&text, now),
+                _ => self.set_status_text("Dropped file isn't a text game record", now),
+            }
+        }
+
+        if self
+            .status_text
+            .as_ref()
+            .is_some_and(|(_, set_time)| now - *set_time > STATUS_DISPLAY_DURATION)
+        {
+            self.status_text = None;
+        }
+
         self.apply_state_specific_updates(now);
     }
 
+    fn set_status_text(&mut self, text: impl Into<String>, now: Timestamp) {
+        self.status_text = Some((text.into(), now));
+    }
+
+    fn export_record(&mut self, now: Timestamp) {
+        let record_text = match &self.state {
+            // Export the whole game, not just the step being viewed
+            GameState::Replay { record, .. } => record.to_text(),
+            _ => GameRecord::new(self.password, &self.history).to_text(),
+        };
+        mq::miniquad::window::clipboard_set(&record_text);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(e) = std::fs::write(RECORD_EXPORT_FILE, &record_text) {
+            self.set_status_text(format!("Copied record, but failed to write file: {e}"), now);
+            return;
+        }
+
+        self.set_status_text("Copied game record to clipboard", now);
+    }
+
+    fn import_record(&mut self, text: &str, now: Timestamp) {
+        match GameRecord::parse(text) {
+            Ok(record) => {
+                self.password = record.password;
+                self.history = record.guesses.clone();
+                let step = record.guesses.len();
+                self.state = GameState::Replay {
+                    record: Box::new(record),
+                    step,
+                };
+                self.set_status_text("Loaded game record", now);
+            }
+            Err(e) => self.set_status_text(format!("Invalid game record: {e}"), now),
+        }
+    }
+
     fn apply_state_specific_updates(&mut self, now: Timestamp) {
         match &mut self.state {
             GameState::InProgress {
@@ -330,7 +422,10 @@ impl MastermindGame {
                 if mq::is_key_pressed(KEY_SUBMIT) || submit_button_action.should_trigger_action() {
                     if let Some(guess) = convert_working_row_if_completed(working_row) {
                         let complete_row = evaluate_guess(guess, *self.password.password());
-                        self.history.push(complete_row);
+                        self.history.push(SubmittedGuess {
+                            row: complete_row,
+                            elapsed: now - *start_time,
+                        });
 
                         if complete_row.num_correct_hits == NUM_SLOTS_PER_ROW {
                             self.state = GameState::Victory {
@@ -410,6 +505,22 @@ impl MastermindGame {
                     self.reset_with_new_password();
                 }
             }
+            GameState::Replay { record, step } => {
+                if mq::is_key_pressed(KEY_REPLAY_STEP_BACKWARD) && *step > 0 {
+                    *step -= 1;
+                } else if mq::is_key_pressed(KEY_REPLAY_STEP_FORWARD)
+                    && *step < record.guesses.len()
+                {
+                    *step += 1;
+                }
+                self.history = record.guesses[..*step].to_vec();
+
+                if mq::is_key_pressed(KEY_REPLAY_PASSWORD) {
+                    self.reset_with_same_password();
+                } else if mq::is_key_pressed(KEY_REPLAY_EXIT) {
+                    self.reset_with_new_password();
+                }
+            }
         }
     }
 
@@ -527,6 +638,7 @@ impl MastermindGame {
             GameState::EditPassword { .. } => board_color,
             GameState::Victory { .. } => mq::GREEN,
             GameState::TooManyGuesses => mq::RED,
+            GameState::Replay { .. } => mq::SKYBLUE,
         };
         mq::draw_rectangle(
             BOARD_OFFSET_X,
@@ -545,7 +657,8 @@ impl MastermindGame {
             }
             GameState::EditPassword { .. }
             | GameState::Victory { .. }
-            | GameState::TooManyGuesses => {
+            | GameState::TooManyGuesses
+            | GameState::Replay { .. } => {
                 for (i, color) in self.password.password().iter().enumerate() {
                     guess_circles_ij::draw(i, 0, *color, self.number_overlay);
                 }
@@ -553,9 +666,9 @@ impl MastermindGame {
         }
 
         // Guesses - colored - history
-        for (j, row) in self.history.iter().enumerate() {
+        for (j, submitted_guess) in self.history.iter().enumerate() {
             let j = NUM_GUESSES - j;
-            for (i, color) in row.guess.iter().enumerate() {
+            for (i, color) in submitted_guess.row.guess.iter().enumerate() {
                 guess_circles_ij::draw(i, j, *color, self.number_overlay);
             }
         }
@@ -589,7 +702,7 @@ impl MastermindGame {
         }
 
         // Keys - colored
-        for (j, row) in self.history.iter().enumerate() {
+        for (j, SubmittedGuess { row, .. }) in self.history.iter().enumerate() {
             let j = (NUM_GUESSES - j) as f32;
             let mut key_offset_index = 0;
             for _ in 0..row.num_correct_hits {
@@ -710,10 +823,14 @@ impl MastermindGame {
                     "Press [number key] to select color\n\
                     Press [{}] to submit guess\n\
                     Press [{}] to toggle numbers display\n\
-                    Press [{}] to edit password",
+                    Press [{}] to edit password\n\
+                    Press [{}] to export game record\n\
+                    Press [{}] to import game record",
                     KEY_SUBMIT.to_lowercase(),
                     KEY_TOGGLE_NUMBER_OVERLAY.to_lowercase(),
                     KEY_PLAYER_EDIT_PASSWORD.to_lowercase(),
+                    KEY_EXPORT_RECORD.to_lowercase(),
+                    KEY_IMPORT_RECORD.to_lowercase(),
                 );
                 bq::draw_text(
                     controls_text,
@@ -765,6 +882,52 @@ impl MastermindGame {
                     Some(info_text_background),
                 );
             }
+            GameState::Replay { record, step } => {
+                // Text - replay position
+                let step_text = match step.checked_sub(1).map(|i| &record.guesses[i]) {
+                    Some(submitted_guess) => format!(
+                        "Guess {step}/{} at {}",
+                        record.guesses.len(),
+                        format_duration(submitted_guess.elapsed)
+                    ),
+                    None => format!("Guess 0/{}", record.guesses.len()),
+                };
+                bq::draw_text(
+                    format!(
+                        "Replay - {step_text}\n\n\
+                        Press [{}]/[{}] to step through guesses.\n\
+                        Press [{}] to play this password.\n\
+                        Press [{}] to exit replay.",
+                        KEY_REPLAY_STEP_BACKWARD.to_lowercase(),
+                        KEY_REPLAY_STEP_FORWARD.to_lowercase(),
+                        KEY_REPLAY_PASSWORD.to_lowercase(),
+                        KEY_REPLAY_EXIT.to_lowercase(),
+                    ),
+                    TextAlignment::Left,
+                    None,
+                    END_GAME_FONT_SIZE,
+                    mq::DARKBLUE,
+                    TextAnchorPoint::window_centered(),
+                    Some(info_text_background),
+                );
+            }
+        }
+
+        // Text - import/export status
+        if let Some((status_text, _)) = &self.status_text {
+            bq::draw_text(
+                status_text,
+                TextAlignment::Left,
+                None,
+                STATUS_FONT_SIZE,
+                mq::WHITE,
+                TextAnchorPoint::window_bottom_left(),
+                Some(TextBackground {
+                    color: mq::BLACK,
+                    x_padding: SEED_TEXT_PADDING,
+                    y_padding: SEED_TEXT_PADDING,
+                }),
+            );
         }
 
         // FPS
@@ -802,7 +965,8 @@ impl MastermindGame {
             match &self.state {
                 GameState::InProgress { .. }
                 | GameState::EditPassword { .. }
-                | GameState::TooManyGuesses => {
+                | GameState::TooManyGuesses
+                | GameState::Replay { .. } => {
                     draw_cursor(mouse_x, mouse_y, self.mouse_color.as_mq());
                 }
                 GameState::Victory {
@@ -837,7 +1001,7 @@ fn draw_cursor(x: f32, y: f32, color: mq::Color) {
 }
 
 mod win_title {
-    use crate::CompleteRow;
+    use crate::SubmittedGuess;
 
     /// (article, title)
     const WIN_TITLES: [(Option<&str>, &str); 8] = [
@@ -856,7 +1020,7 @@ mod win_title {
         pub(crate) title: &'static str,
     }
 
-    pub(crate) fn get(history: &[CompleteRow]) -> WinTitle {
+    pub(crate) fn get(history: &[SubmittedGuess]) -> WinTitle {
         let (article, title) = *WIN_TITLES
             .get(history.len() - 1)
             .unwrap_or(WIN_TITLES.last().unwrap());
@@ -1106,6 +1270,36 @@ impl Color {
         palette[index]
     }
 
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Red => "red",
+            Self::Orange => "orange",
+            Self::Yellow => "yellow",
+            Self::Green => "green",
+            Self::Blue => "blue",
+            Self::Purple => "purple",
+            Self::Pink => "pink",
+            Self::LightBlue => "lightblue",
+            Self::NeonGreen => "neongreen",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Red,
+            Self::Orange,
+            Self::Yellow,
+            Self::Green,
+            Self::Blue,
+            Self::Purple,
+            Self::Pink,
+            Self::LightBlue,
+            Self::NeonGreen,
+        ]
+        .into_iter()
+        .find(|c| c.name() == name)
+    }
+
     fn as_mq(&self) -> mq::Color {
         match self {
             Self::Red => mq::RED,
@@ -1128,6 +1322,13 @@ struct CompleteRow {
     num_misplaced_hits: usize,
 }
 
+#[derive(Copy, Clone)]
+struct SubmittedGuess {
+    row: CompleteRow,
+    // Time since the game started
+    elapsed: Duration,
+}
+
 // None => Incomplete row
 // Some => Completed row
 fn convert_working_row_if_completed(
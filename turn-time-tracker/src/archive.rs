@@ -0,0 +1,133 @@
+//! Finished sessions, kept for the leaderboard across game nights.
+use crate::TurnTimeTracker;
+use better_quad::bq::{self, Timestamp};
+use macroquad::prelude as mq;
+use nanoserde::{DeJson, SerJson};
+use std::time::Duration;
+
+const ARCHIVE_KEY: &str = "turn-time-tracker-archive";
+const SAVED_GAME_NAME_KEY: &str = "turn-time-tracker-game";
+
+/// Oldest sessions are dropped past this, to keep the archive quick to load and save.
+const MAX_ARCHIVED_SESSIONS: usize = 500;
+
+pub(crate) const MAX_GAME_NAME_LEN: usize = 24;
+
+// `pub` since nanoserde derives don't support `pub(crate)`. The module is private anyway.
+#[derive(SerJson, DeJson, Default)]
+pub struct Archive {
+    /// Oldest first.
+    pub sessions: Vec<ArchivedSession>,
+}
+
+#[derive(SerJson, DeJson, Clone, PartialEq, Debug)]
+pub struct ArchivedSession {
+    /// Empty if no game name was given.
+    pub game: String,
+    /// UTC `YYYY-MM-DD` of when the session was finished.
+    pub date: String,
+    pub players: Vec<ArchivedPlayer>,
+}
+
+#[derive(SerJson, DeJson, Clone, PartialEq, Debug)]
+pub struct ArchivedPlayer {
+    pub name: String,
+    /// Includes time from shared turns.
+    pub total_ms: u64,
+    /// Time in the player's own turns, which the average turn is based on.
+    pub turns_ms: u64,
+    pub num_turns: usize,
+}
+
+impl ArchivedSession {
+    pub(crate) fn capture(tracker: &TurnTimeTracker, now: Timestamp) -> Self {
+        Self {
+            game: tracker.game_name.clone(),
+            date: now.utc_date_str(),
+            players: tracker
+                .players
+                .raw()
+                .0
+                .iter()
+                .map(|player| ArchivedPlayer {
+                    name: player.display_name.clone(),
+                    total_ms: player.total_time.as_millis() as u64,
+                    turns_ms: (player.total_time - player.shared_time).as_millis() as u64,
+                    num_turns: player.stats.num_turns(),
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn total_time(&self) -> Duration {
+        Duration::from_millis(self.players.iter().map(|player| player.total_ms).sum())
+    }
+}
+
+/// All archived sessions, or none if there's no archive yet (or it can't be read).
+pub(crate) fn load() -> Archive {
+    let Some(json) = bq::storage_load(ARCHIVE_KEY) else {
+        return Archive::default();
+    };
+    Archive::deserialize_json(&json).unwrap_or_else(|e| {
+        mq::warn!("Ignoring session archive: {e}");
+        Archive::default()
+    })
+}
+
+pub(crate) fn add_session(session: ArchivedSession) -> Result<(), String> {
+    let json = with_session_added(bq::storage_load(ARCHIVE_KEY).as_deref(), session)?;
+    bq::storage_store(ARCHIVE_KEY, &json)
+}
+
+/// The archive stored as `opt_json` with `session` added, as JSON. An archive that can't be read is
+/// an error rather than starting over, so its sessions aren't lost by saving over it.
+fn with_session_added(opt_json: Option<&str>, session: ArchivedSession) -> Result<String, String> {
+    let mut archive = match opt_json {
+        Some(json) => Archive::deserialize_json(json)
+            .map_err(|e| format!("the existing archive can't be read ({e})"))?,
+        None => Archive::default(),
+    };
+    archive.sessions.push(session);
+    let num_dropped = archive.sessions.len().saturating_sub(MAX_ARCHIVED_SESSIONS);
+    archive.sessions.drain(..num_dropped);
+    Ok(archive.serialize_json())
+}
+
+/// Game name from the last time tracking was started, if any.
+pub(crate) fn load_saved_game_name() -> String {
+    bq::storage_load(SAVED_GAME_NAME_KEY).unwrap_or_default()
+}
+
+pub(crate) fn save_game_name(game_name: &str) {
+    if let Err(e) = bq::storage_store(SAVED_GAME_NAME_KEY, game_name) {
+        mq::warn!("Failed to save game name: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{with_session_added, Archive, ArchivedPlayer, ArchivedSession};
+    use nanoserde::DeJson;
+
+    #[test]
+    fn test_add_session() {
+        let session = ArchivedSession {
+            game: "Catan".to_string(),
+            date: "2026-10-18".to_string(),
+            players: vec![ArchivedPlayer {
+                name: "Leo".to_string(),
+                total_ms: 5000,
+                turns_ms: 4000,
+                num_turns: 3,
+            }],
+        };
+        let json = with_session_added(None, session.clone()).unwrap();
+        let json = with_session_added(Some(&json), session.clone()).unwrap();
+        let archive = Archive::deserialize_json(&json).unwrap();
+        assert_eq!(vec![session.clone(), session.clone()], archive.sessions);
+
+        // Not saved over, so the sessions in it can still be recovered.
+        assert!(with_session_added(Some("{\"sessions\": [{"), session).is_err());
+    }
+}
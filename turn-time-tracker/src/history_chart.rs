@@ -0,0 +1,229 @@
+//! Chart view of the session so far: every turn as a bar in its player's lane over time (a Gantt
+//! strip), and each player's cumulative time as a line.
+use crate::turn_log::LoggedTurn;
+use crate::{format_duration_stats, Player};
+use better_quad::bq::{self, Chart, TextAlignment, TextAnchorPoint};
+use macroquad::prelude as mq;
+use std::time::Duration;
+
+// Draw consts
+const CHART_X: f32 = 130.0;
+const CHART_RIGHT_MARGIN: f32 = 30.0;
+const TITLE_FONT_SIZE: u16 = 26;
+const LABEL_FONT_SIZE: u16 = 20;
+const AXIS_THICKNESS: f32 = 2.0;
+const AXIS_COLOR: mq::Color = mq::BLACK;
+
+const TIMELINE_TITLE_Y: f32 = 60.0;
+const TIMELINE_Y: f32 = TIMELINE_TITLE_Y + 40.0;
+const TIMELINE_LANE_HEIGHT: f32 = 30.0;
+/// Fraction of a lane left empty above and below the bars.
+const TIMELINE_LANE_PADDING: f32 = 0.15;
+
+/// Below the bottom of the timeline's plot area.
+const CUMULATIVE_TITLE_GAP: f32 = 60.0;
+/// The cumulative chart fills the rest of the window down to this margin, but is at least the min.
+const CUMULATIVE_CHART_BOTTOM_MARGIN: f32 = 60.0;
+const CUMULATIVE_CHART_MIN_HEIGHT: f32 = 150.0;
+const CUMULATIVE_LINE_THICKNESS: f32 = 3.0;
+
+/// Tick spacing is the smallest of these that fits in `MAX_TIME_TICKS` ticks.
+const TIME_TICK_STEPS: [Duration; 10] = [
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+    Duration::from_secs(2 * 60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(10 * 60),
+    Duration::from_secs(15 * 60),
+    Duration::from_secs(30 * 60),
+    Duration::from_secs(60 * 60),
+    Duration::from_secs(2 * 60 * 60),
+];
+const MAX_TIME_TICKS: usize = 6;
+
+/// `turns` are in the order played, see [crate::turn_log::TurnLog::turns].
+pub(crate) fn draw_history_chart(players: &[Player], turns: &[LoggedTurn]) {
+    let session_time = turns.iter().map(|turn| turn.duration).sum::<Duration>();
+    let chart_width = mq::screen_width() - CHART_X - CHART_RIGHT_MARGIN;
+
+    // Timeline, first player in the top lane.
+    draw_title("Turns", TIMELINE_TITLE_Y);
+    let num_lanes = players.len() as f32;
+    let timeline = Chart::new(
+        mq::Rect::new(
+            CHART_X,
+            TIMELINE_Y,
+            chart_width,
+            TIMELINE_LANE_HEIGHT * num_lanes,
+        ),
+        session_time.as_secs_f32(),
+        num_lanes,
+    );
+    let lane_bottom = |player_index: usize| num_lanes - 1.0 - player_index as f32;
+    let mut start_offset = Duration::ZERO;
+    for turn in turns {
+        let end_offset = start_offset + turn.duration;
+        timeline.draw_bar(
+            start_offset.as_secs_f32(),
+            end_offset.as_secs_f32(),
+            lane_bottom(turn.player_index) + TIMELINE_LANE_PADDING,
+            lane_bottom(turn.player_index) + 1.0 - TIMELINE_LANE_PADDING,
+            players[turn.player_index].display_color,
+        );
+        start_offset = end_offset;
+    }
+    timeline.draw_axes(AXIS_THICKNESS, AXIS_COLOR);
+    for (i, player) in players.iter().enumerate() {
+        timeline.draw_y_label(
+            lane_bottom(i) + 0.5,
+            &player.display_name,
+            LABEL_FONT_SIZE,
+            player.display_color,
+        );
+    }
+    draw_time_ticks(&timeline, session_time);
+
+    // Cumulative time per player.
+    let timeline_bottom = timeline.plot_rect().bottom();
+    draw_title("Total time", timeline_bottom + CUMULATIVE_TITLE_GAP);
+    // Of the logged turns, like the lines, so not shared turns or the turn in progress.
+    let max_total_time = (0..players.len())
+        .map(|i| {
+            turns
+                .iter()
+                .filter(|turn| turn.player_index == i)
+                .map(|turn| turn.duration)
+                .sum::<Duration>()
+        })
+        .max()
+        .unwrap_or_default();
+    let cumulative_y = timeline_bottom + CUMULATIVE_TITLE_GAP + (TIMELINE_Y - TIMELINE_TITLE_Y);
+    let cumulative = Chart::new(
+        mq::Rect::new(
+            CHART_X,
+            cumulative_y,
+            chart_width,
+            (mq::screen_height() - CUMULATIVE_CHART_BOTTOM_MARGIN - cumulative_y)
+                .max(CUMULATIVE_CHART_MIN_HEIGHT),
+        ),
+        session_time.as_secs_f32(),
+        max_total_time.as_secs_f32(),
+    );
+    for (i, player) in players.iter().enumerate() {
+        cumulative.draw_line(
+            &cumulative_time_points(i, turns),
+            CUMULATIVE_LINE_THICKNESS,
+            player.display_color,
+        );
+    }
+    cumulative.draw_axes(AXIS_THICKNESS, AXIS_COLOR);
+    for tick in time_ticks(max_total_time) {
+        cumulative.draw_y_label(
+            tick.as_secs_f32(),
+            format_duration_stats(Some(tick)),
+            LABEL_FONT_SIZE,
+            AXIS_COLOR,
+        );
+    }
+    draw_time_ticks(&cumulative, session_time);
+}
+
+fn draw_title(title: &str, y: f32) {
+    bq::draw_text(
+        title,
+        TextAlignment::Left,
+        None,
+        TITLE_FONT_SIZE,
+        AXIS_COLOR,
+        TextAnchorPoint::TopLeft { x: CHART_X, y },
+        None,
+    );
+}
+
+fn draw_time_ticks(chart: &Chart, session_time: Duration) {
+    for tick in time_ticks(session_time) {
+        chart.draw_x_label(
+            tick.as_secs_f32(),
+            format_duration_stats(Some(tick)),
+            LABEL_FONT_SIZE,
+            AXIS_COLOR,
+        );
+    }
+}
+
+/// Evenly spaced ticks from 0 up to `max`.
+fn time_ticks(max: Duration) -> Vec<Duration> {
+    let step = TIME_TICK_STEPS
+        .into_iter()
+        .find(|step| max.as_secs_f32() / step.as_secs_f32() < MAX_TIME_TICKS as f32)
+        .unwrap_or(TIME_TICK_STEPS[TIME_TICK_STEPS.len() - 1]);
+    std::iter::successors(Some(Duration::ZERO), |tick| Some(*tick + step))
+        .take_while(|tick| *tick <= max)
+        .collect()
+}
+
+/// (session time, player's total time) in seconds, at each start and end of the player's turns.
+fn cumulative_time_points(player_index: usize, turns: &[LoggedTurn]) -> Vec<(f32, f32)> {
+    let mut points = vec![(0.0, 0.0)];
+    let mut start_offset = Duration::ZERO;
+    let mut total_time = Duration::ZERO;
+    for turn in turns {
+        if turn.player_index == player_index {
+            points.push((start_offset.as_secs_f32(), total_time.as_secs_f32()));
+            total_time += turn.duration;
+            points.push((
+                (start_offset + turn.duration).as_secs_f32(),
+                total_time.as_secs_f32(),
+            ));
+        }
+        start_offset += turn.duration;
+    }
+    points.push((start_offset.as_secs_f32(), total_time.as_secs_f32()));
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cumulative_time_points, time_ticks};
+    use crate::turn_log::LoggedTurn;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cumulative_time_points() {
+        let turn = |player_index, seconds| LoggedTurn {
+            player_index,
+            round: 1,
+            duration: Duration::from_secs(seconds),
+            counted: true,
+        };
+        let turns = [turn(0, 10), turn(1, 5), turn(0, 20)];
+
+        assert_eq!(
+            vec![
+                (0.0, 0.0),
+                (0.0, 0.0),
+                (10.0, 10.0),
+                (15.0, 10.0),
+                (35.0, 30.0),
+                (35.0, 30.0)
+            ],
+            cumulative_time_points(0, &turns)
+        );
+        assert_eq!(
+            vec![(0.0, 0.0), (10.0, 0.0), (15.0, 5.0), (35.0, 5.0)],
+            cumulative_time_points(1, &turns)
+        );
+    }
+
+    #[test]
+    fn test_time_ticks() {
+        let secs = Duration::from_secs;
+        assert_eq!(vec![secs(0)], time_ticks(secs(0)));
+        assert_eq!(vec![secs(0), secs(10), secs(20)], time_ticks(secs(25)));
+        assert_eq!(
+            vec![secs(0), secs(300), secs(600), secs(900)],
+            time_ticks(secs(1000))
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Players given at launch, as an alternative to the setup screen.
+//!
+//! * Native: `--roster <file.toml|file.json>` and/or repeated `--player "Name:#RRGGBB"` arguments.
+//! * WASM: repeated `player=Name:%23RRGGBB` URL query params.
+use crate::roster::{self, RosterPlayer};
+
+/// `Ok(None)` if no players were given at launch.
+pub(crate) fn roster_from_launch_args() -> Result<Option<Vec<RosterPlayer>>, String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let opt_players = parse_cli_args(std::env::args().skip(1), |path| {
+        std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))
+    })?;
+    #[cfg(target_arch = "wasm32")]
+    let opt_players = parse_query_params(better_quad::bq::url_query_params())?;
+
+    if let Some(players) = &opt_players {
+        roster::validate(players)?;
+    }
+    Ok(opt_players)
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+fn parse_cli_args(
+    mut args: impl Iterator<Item = String>,
+    read_file: impl Fn(&str) -> Result<String, String>,
+) -> Result<Option<Vec<RosterPlayer>>, String> {
+    let mut opt_players: Option<Vec<RosterPlayer>> = None;
+    while let Some(arg) = args.next() {
+        let new_players = match arg.as_str() {
+            "--player" => {
+                let value = args.next().ok_or("--player requires \"Name:#RRGGBB\"")?;
+                vec![RosterPlayer::parse(&value)?]
+            }
+            "--roster" => {
+                let path = args.next().ok_or("--roster requires a file path")?;
+                roster::parse_file(&path, &read_file(&path)?)?
+            }
+            _ => {
+                return Err(format!(
+                    "unknown argument '{arg}' (expected --player or --roster)"
+                ))
+            }
+        };
+        opt_players.get_or_insert_with(Vec::new).extend(new_players);
+    }
+
+    Ok(opt_players)
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+fn parse_query_params(params: Vec<(String, String)>) -> Result<Option<Vec<RosterPlayer>>, String> {
+    let players = params
+        .iter()
+        // Ignore other params, the page may be linked with unrelated ones.
+        .filter(|(key, _)| key == "player")
+        .map(|(_, value)| RosterPlayer::parse(value))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(if players.is_empty() {
+        None
+    } else {
+        Some(players)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_cli_args, parse_query_params};
+    use crate::roster::RosterPlayer;
+
+    fn names(opt_players: Option<Vec<RosterPlayer>>) -> Option<Vec<String>> {
+        opt_players.map(|players| players.into_iter().map(|p| p.name).collect())
+    }
+
+    fn cli(args: &[&str]) -> Result<Option<Vec<String>>, String> {
+        let read_file = |path: &str| match path {
+            "roster.toml" => Ok("[[players]]\nname = \"B\"\ncolor = \"#0000FF\"\n".to_string()),
+            _ => Err(format!("{path}: No such file or directory")),
+        };
+        parse_cli_args(args.iter().map(|a| a.to_string()), read_file).map(names)
+    }
+
+    #[test]
+    fn test_parse_cli_args() {
+        assert_eq!(Ok(None), cli(&[]));
+        assert_eq!(
+            Ok(Some(vec!["A".to_string(), "B".to_string()])),
+            cli(&["--player", "A:#FF0000", "--roster", "roster.toml"])
+        );
+
+        let test_cases = [
+            // (args, expected error)
+            (vec!["--player"], "--player requires \"Name:#RRGGBB\""),
+            (
+                vec!["--player", "A:red"],
+                "'red' isn't a hex color like #FF8800 (player 'A')",
+            ),
+            (
+                vec!["--roster", "missing.toml"],
+                "missing.toml: No such file or directory",
+            ),
+            (
+                vec!["A:#FF0000"],
+                "unknown argument 'A:#FF0000' (expected --player or --roster)",
+            ),
+        ];
+        for (args, expected_error) in test_cases {
+            assert_eq!(Err(expected_error.to_string()), cli(&args), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_query_params() {
+        let params = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            Ok(None),
+            parse_query_params(params(&[("utm_source", "x")])).map(names)
+        );
+        assert_eq!(
+            Ok(Some(vec!["A".to_string(), "B".to_string()])),
+            parse_query_params(params(&[("player", "A:#FF0000"), ("player", "B:#0000FF")]))
+                .map(names)
+        );
+        assert!(parse_query_params(params(&[("player", "A")])).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! Where the pie and player text go, scaled to the window. Wide windows put the player text beside
+//! the pie, tall windows put it underneath. Either way, a toolbar of buttons is along the bottom.
+use macroquad::prelude as mq;
+
+const MARGIN: f32 = 10.0;
+/// Share of a tall window's height given to the pie, the rest going to the player text.
+const STACKED_PIE_HEIGHT_SHARE: f32 = 0.55;
+/// Pie radius relative to half the pie area, leaving room for the current player's bigger slice.
+const PIE_RADIUS_FRACTION: f32 = 0.75;
+pub(crate) const PIE_RADIUS_CURRENT_TURN_MULTIPLIER: f32 = 1.2;
+
+/// Player text is drawn at this size when it fits, and shrinks down to the min when it doesn't.
+pub(crate) const MAX_FONT_SIZE: f32 = 40.0;
+const MIN_FONT_SIZE: f32 = 10.0;
+/// Gap between lines of player text, relative to the font size.
+const LINE_GAP: f32 = 0.25;
+/// Header font size relative to the pie area's size.
+const HEADER_FONT_FRACTION: f32 = 1.0 / 15.0;
+/// Toolbar height relative to the window height, within the min and max.
+const TOOLBAR_HEIGHT_FRACTION: f32 = 0.06;
+const TOOLBAR_MIN_HEIGHT: f32 = 30.0;
+const TOOLBAR_MAX_HEIGHT: f32 = 60.0;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Layout {
+    /// Tapping anywhere in here passes the turn.
+    pub(crate) pie_area: mq::Rect,
+    pub(crate) pie_center: mq::Vec2,
+    pub(crate) pie_radius: f32,
+    pub(crate) header_font_size: f32,
+    pub(crate) text_x: f32,
+    /// Top of the player text area.
+    text_y: f32,
+    pub(crate) text_font_size: f32,
+    toolbar: mq::Rect,
+}
+
+impl Layout {
+    /// Fits `num_lines` lines of player text, the widest being `widest_line_width` pixels wide
+    /// when drawn at [MAX_FONT_SIZE].
+    pub(crate) fn new(
+        screen_width: f32,
+        screen_height: f32,
+        num_lines: usize,
+        widest_line_width: f32,
+    ) -> Self {
+        let toolbar_height =
+            (screen_height * TOOLBAR_HEIGHT_FRACTION).clamp(TOOLBAR_MIN_HEIGHT, TOOLBAR_MAX_HEIGHT);
+        let toolbar = mq::Rect::new(
+            0.0,
+            screen_height - toolbar_height,
+            screen_width,
+            toolbar_height,
+        );
+        let screen_height = screen_height - toolbar_height;
+
+        let (pie_area, text_area) = if screen_width > screen_height {
+            let pie_size = screen_height.min(screen_width / 2.0);
+            (
+                mq::Rect::new(0.0, 0.0, pie_size, pie_size),
+                mq::Rect::new(pie_size, 0.0, screen_width - pie_size, screen_height),
+            )
+        } else {
+            let pie_size = screen_width.min(screen_height * STACKED_PIE_HEIGHT_SHARE);
+            (
+                mq::Rect::new((screen_width - pie_size) / 2.0, 0.0, pie_size, pie_size),
+                mq::Rect::new(0.0, pie_size, screen_width, screen_height - pie_size),
+            )
+        };
+
+        let fit_width_font_size = if widest_line_width > 0.0 {
+            MAX_FONT_SIZE * (text_area.w - 2.0 * MARGIN) / widest_line_width
+        } else {
+            MAX_FONT_SIZE
+        };
+        let fit_height_font_size =
+            (text_area.h - MARGIN) / (num_lines.max(1) as f32 * (1.0 + LINE_GAP));
+        let text_font_size = MAX_FONT_SIZE
+            .min(fit_width_font_size)
+            .min(fit_height_font_size)
+            .max(MIN_FONT_SIZE);
+
+        Self {
+            pie_area,
+            pie_center: pie_area.center(),
+            pie_radius: pie_area.w / 2.0 * PIE_RADIUS_FRACTION,
+            header_font_size: (pie_area.w * HEADER_FONT_FRACTION)
+                .clamp(MIN_FONT_SIZE, MAX_FONT_SIZE),
+            text_x: text_area.x + MARGIN,
+            text_y: text_area.y,
+            text_font_size,
+            toolbar,
+        }
+    }
+
+    /// Bounds of button `i` of `num_buttons` across the toolbar.
+    pub(crate) fn toolbar_button(&self, i: usize, num_buttons: usize) -> mq::Rect {
+        let width = (self.toolbar.w - MARGIN) / num_buttons as f32;
+        mq::Rect::new(
+            self.toolbar.x + MARGIN + width * i as f32,
+            self.toolbar.y,
+            width - MARGIN,
+            self.toolbar.h - MARGIN,
+        )
+    }
+
+    /// Bottom of everything but the toolbar, e.g. for status text.
+    pub(crate) fn toolbar_top(&self) -> f32 {
+        self.toolbar.y
+    }
+
+    pub(crate) fn line_height(&self) -> f32 {
+        self.text_font_size * (1.0 + LINE_GAP)
+    }
+
+    /// Baseline of line `i` of player text.
+    pub(crate) fn text_line_y(&self, i: usize) -> f32 {
+        self.text_y + self.line_height() * (i as f32 + 1.0)
+    }
+
+    /// Baseline of the round header in the top left.
+    pub(crate) fn header_y(&self) -> f32 {
+        MARGIN + self.header_font_size
+    }
+
+    /// Baseline of the paused text, under the round header.
+    pub(crate) fn paused_text_y(&self) -> f32 {
+        self.header_y() + self.header_font_size * (1.0 + LINE_GAP)
+    }
+
+    /// Header and paused text x.
+    pub(crate) fn corner_text_x(&self) -> f32 {
+        MARGIN
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Layout, MAX_FONT_SIZE, MIN_FONT_SIZE};
+    use macroquad::prelude as mq;
+
+    #[test]
+    fn test_layout() {
+        // Tall window: pie on top, text underneath at full size, toolbar at the bottom.
+        let tall = Layout::new(600.0, 1060.0, 5, 400.0);
+        assert_eq!(mq::vec2(300.0, 275.0), tall.pie_center);
+        assert_eq!(MAX_FONT_SIZE, tall.text_font_size);
+        assert!(tall.text_line_y(0) > 550.0);
+        assert_eq!(1000.0, tall.toolbar_top());
+        assert_eq!(
+            mq::Rect::new(305.0, 1000.0, 285.0, 50.0),
+            tall.toolbar_button(1, 2)
+        );
+
+        // Wide window: pie on the left, text to the right.
+        let wide = Layout::new(1200.0, 660.0, 5, 400.0);
+        assert_eq!(mq::vec2(300.0, 300.0), wide.pie_center);
+        assert_eq!(610.0, wide.text_x);
+        assert!(wide.text_line_y(0) < 100.0);
+
+        // Long lines shrink the font to fit across.
+        let long_lines = Layout::new(600.0, 1060.0, 5, 1160.0);
+        assert_eq!(MAX_FONT_SIZE / 2.0, long_lines.text_font_size);
+
+        // Tiny windows bottom out at the min size.
+        let tiny = Layout::new(100.0, 100.0, 10, 1000.0);
+        assert_eq!(MIN_FONT_SIZE, tiny.text_font_size);
+    }
+}
@@ -0,0 +1,337 @@
+//! Leaderboard of everyone who played in the archived sessions, matched up by name.
+use crate::archive::{Archive, ArchivedPlayer, ArchivedSession};
+use crate::{format_duration_concise, format_duration_stats};
+use better_quad::bq::{self, SimpleButton, TextAlignment, TextAnchorPoint};
+use macroquad::prelude as mq;
+use std::cmp::Ordering;
+use std::time::Duration;
+
+// Control consts
+const KEY_BACK: mq::KeyCode = mq::KeyCode::Escape;
+
+/// A person's trend compares their last sessions to the ones before.
+const NUM_RECENT_SESSIONS: usize = 3;
+const NUM_SHOWN_RECENT_SESSIONS: usize = 5;
+
+// Draw consts
+const LEADERBOARD_X: f32 = 20.0;
+const TITLE_FONT_SIZE: u16 = 40;
+const TITLE_Y: f32 = 20.0;
+const TABLE_Y: f32 = 110.0;
+const LINE_HEIGHT: f32 = 32.0;
+const FONT_SIZE: u16 = 26;
+const HELP_FONT_SIZE: u16 = 22;
+/// Offsets from [LEADERBOARD_X] of the name, games, average turn, time share, and trend columns.
+const COLUMN_OFFSETS: [f32; 5] = [0.0, 180.0, 260.0, 370.0, 460.0];
+const FASTER_COLOR: mq::Color = mq::DARKGREEN;
+const SLOWER_COLOR: mq::Color = mq::MAROON;
+const BACK_BUTTON_WIDTH: f32 = 120.0;
+const BACK_BUTTON_HEIGHT: f32 = 50.0;
+const BACK_BUTTON_FONT_SIZE: u16 = 30;
+const BUTTON_BORDER_THICKNESS: f32 = 2.0;
+
+pub(crate) struct LeaderboardRow {
+    pub(crate) name: String,
+    pub(crate) num_sessions: usize,
+    pub(crate) average_turn: Option<Duration>,
+    /// Average of the person's share of each of their sessions' total time.
+    pub(crate) time_share: f32,
+    /// Relative change of the average turn over the last [NUM_RECENT_SESSIONS] sessions compared
+    /// to the sessions before those, e.g. `-0.1` for turns 10% quicker lately. `None` until there
+    /// are enough sessions to compare.
+    pub(crate) opt_trend: Option<f32>,
+}
+
+/// A row per person, quickest average turn first.
+pub(crate) fn leaderboard(sessions: &[ArchivedSession]) -> Vec<LeaderboardRow> {
+    // Each person's sessions, oldest first, with the session's total time. Names are matched
+    // ignoring case like in the roster, and shown as first archived.
+    let mut sessions_by_name: Vec<(&str, Vec<(&ArchivedPlayer, Duration)>)> = Vec::new();
+    for session in sessions {
+        let session_total_time = session.total_time();
+        for player in &session.players {
+            let entry = (player, session_total_time);
+            match sessions_by_name
+                .iter_mut()
+                .find(|(name, _)| name.eq_ignore_ascii_case(&player.name))
+            {
+                Some((_, entries)) => entries.push(entry),
+                None => sessions_by_name.push((&player.name, vec![entry])),
+            }
+        }
+    }
+
+    let mut rows = sessions_by_name
+        .into_iter()
+        .map(|(name, entries)| {
+            let time_share = entries
+                .iter()
+                .map(|(player, session_total_time)| {
+                    if session_total_time.is_zero() {
+                        0.0
+                    } else {
+                        player.total_ms as f32 / session_total_time.as_millis() as f32
+                    }
+                })
+                .sum::<f32>()
+                / entries.len() as f32;
+            let opt_trend = entries
+                .len()
+                .checked_sub(NUM_RECENT_SESSIONS)
+                .filter(|num_earlier| *num_earlier > 0)
+                .and_then(|num_earlier| {
+                    let (earlier, recent) = entries.split_at(num_earlier);
+                    let earlier_average = average_turn(earlier)?;
+                    let recent_average = average_turn(recent)?;
+                    Some(recent_average.as_secs_f32() / earlier_average.as_secs_f32() - 1.0)
+                });
+            LeaderboardRow {
+                name: name.to_string(),
+                num_sessions: entries.len(),
+                average_turn: average_turn(&entries),
+                time_share,
+                opt_trend,
+            }
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by(|a, b| match (a.average_turn, b.average_turn) {
+        (Some(a_turn), Some(b_turn)) => a_turn.cmp(&b_turn).then_with(|| a.name.cmp(&b.name)),
+        // People without any turns go last.
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.name.cmp(&b.name),
+    });
+    rows
+}
+
+/// Average over all of the turns in `entries`, so longer sessions weigh more. `None` if there
+/// weren't any turns, or they took no time.
+fn average_turn(entries: &[(&ArchivedPlayer, Duration)]) -> Option<Duration> {
+    let turns_ms = entries
+        .iter()
+        .map(|(player, _)| player.turns_ms)
+        .sum::<u64>();
+    let num_turns = entries
+        .iter()
+        .map(|(player, _)| player.num_turns as u64)
+        .sum::<u64>();
+    (num_turns > 0 && turns_ms > 0).then(|| Duration::from_millis(turns_ms / num_turns))
+}
+
+pub(crate) struct LeaderboardScreen {
+    rows: Vec<LeaderboardRow>,
+    num_sessions: usize,
+    /// Newest first.
+    recent_sessions: Vec<ArchivedSession>,
+    back_button: SimpleButton,
+}
+
+impl LeaderboardScreen {
+    pub(crate) fn new(archive: Archive) -> Self {
+        Self {
+            rows: leaderboard(&archive.sessions),
+            num_sessions: archive.sessions.len(),
+            recent_sessions: archive
+                .sessions
+                .into_iter()
+                .rev()
+                .take(NUM_SHOWN_RECENT_SESSIONS)
+                .collect(),
+            back_button: SimpleButton::new(0.0, 0.0, 0.0, 0.0),
+        }
+    }
+
+    /// Returns whether to go back to the setup screen.
+    pub(crate) fn update(&mut self) -> bool {
+        self.back_button.set_bounds(
+            mq::screen_width() - LEADERBOARD_X - BACK_BUTTON_WIDTH,
+            TITLE_Y,
+            BACK_BUTTON_WIDTH,
+            BACK_BUTTON_HEIGHT,
+        );
+        let back_clicked = self.back_button.tick_state().should_trigger_action();
+        back_clicked || mq::is_key_pressed(KEY_BACK)
+    }
+
+    pub(crate) fn draw(&self) {
+        mq::clear_background(mq::LIGHTGRAY);
+
+        bq::draw_text(
+            "Leaderboard",
+            TextAlignment::Left,
+            None,
+            TITLE_FONT_SIZE,
+            mq::BLACK,
+            TextAnchorPoint::TopLeft {
+                x: LEADERBOARD_X,
+                y: TITLE_Y,
+            },
+            None,
+        );
+        let back_color = if self.back_button.is_pressed() {
+            mq::GRAY
+        } else {
+            mq::WHITE
+        };
+        self.back_button.draw(
+            back_color,
+            mq::BLACK,
+            BUTTON_BORDER_THICKNESS,
+            "Back",
+            BACK_BUTTON_FONT_SIZE,
+            mq::BLACK,
+        );
+
+        if self.rows.is_empty() {
+            mq::draw_text(
+                "No finished sessions yet.",
+                LEADERBOARD_X,
+                TABLE_Y,
+                FONT_SIZE as f32,
+                mq::BLACK,
+            );
+            self.draw_help_text(TABLE_Y + LINE_HEIGHT);
+            return;
+        }
+
+        let draw_row = |i: usize, cells: [(String, mq::Color); 5]| {
+            for ((text, color), offset) in cells.into_iter().zip(COLUMN_OFFSETS) {
+                mq::draw_text(
+                    &text,
+                    LEADERBOARD_X + offset,
+                    TABLE_Y + LINE_HEIGHT * i as f32,
+                    FONT_SIZE as f32,
+                    color,
+                );
+            }
+        };
+        let header = ["Name", "Games", "Avg turn", "Share", "Trend"];
+        draw_row(0, header.map(|text| (text.to_string(), mq::DARKGRAY)));
+        for (i, row) in self.rows.iter().enumerate() {
+            let trend_cell = match row.opt_trend {
+                Some(trend) => (
+                    format!("{:+.0}%", 100.0 * trend),
+                    if trend <= 0.0 {
+                        FASTER_COLOR
+                    } else {
+                        SLOWER_COLOR
+                    },
+                ),
+                None => ("-".to_string(), mq::DARKGRAY),
+            };
+            draw_row(
+                i + 1,
+                [
+                    (row.name.clone(), mq::BLACK),
+                    (row.num_sessions.to_string(), mq::BLACK),
+                    (format_duration_stats(row.average_turn), mq::BLACK),
+                    (format!("{:.0}%", 100.0 * row.time_share), mq::BLACK),
+                    trend_cell,
+                ],
+            );
+        }
+
+        let recent_y = TABLE_Y + LINE_HEIGHT * (self.rows.len() as f32 + 1.5);
+        mq::draw_text(
+            format!("Recent sessions (of {})", self.num_sessions),
+            LEADERBOARD_X,
+            recent_y,
+            FONT_SIZE as f32,
+            mq::DARKGRAY,
+        );
+        for (i, session) in self.recent_sessions.iter().enumerate() {
+            let game = if session.game.is_empty() {
+                "(no game name)"
+            } else {
+                &session.game
+            };
+            mq::draw_text(
+                format!(
+                    "{}  {game}, {} players, {}",
+                    session.date,
+                    session.players.len(),
+                    format_duration_concise(session.total_time())
+                ),
+                LEADERBOARD_X,
+                recent_y + LINE_HEIGHT * (i + 1) as f32,
+                FONT_SIZE as f32,
+                mq::BLACK,
+            );
+        }
+        self.draw_help_text(recent_y + LINE_HEIGHT * (self.recent_sessions.len() as f32 + 1.0));
+    }
+
+    fn draw_help_text(&self, y: f32) {
+        bq::draw_text(
+            format!(
+                "Share is of each session's total time, averaged over sessions.\n\
+                Trend is the change in average turn over the last {NUM_RECENT_SESSIONS} sessions.\n\
+                Finish a game with [f] in the tracker to add it here."
+            ),
+            TextAlignment::Left,
+            None,
+            HELP_FONT_SIZE,
+            mq::DARKGRAY,
+            TextAnchorPoint::TopLeft {
+                x: LEADERBOARD_X,
+                y,
+            },
+            None,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::leaderboard;
+    use crate::archive::{ArchivedPlayer, ArchivedSession};
+    use std::time::Duration;
+
+    #[test]
+    fn test_leaderboard() {
+        let player = |name: &str, total_s: u64, turns_s: u64, num_turns| ArchivedPlayer {
+            name: name.to_string(),
+            total_ms: total_s * 1000,
+            turns_ms: turns_s * 1000,
+            num_turns,
+        };
+        let session = |players| ArchivedSession {
+            game: "Catan".to_string(),
+            date: "2026-10-18".to_string(),
+            players,
+        };
+        // Ann's turns get quicker over the last 3 sessions, Bob only played twice (once as "bob").
+        let sessions = [
+            session(vec![
+                player("Ann", 600, 600, 10),
+                player("Bob", 200, 200, 10),
+            ]),
+            session(vec![player("Ann", 300, 300, 5), player("Cy", 300, 300, 5)]),
+            session(vec![
+                player("Ann", 300, 300, 10),
+                player("bob", 100, 100, 10),
+            ]),
+            session(vec![player("Ann", 300, 300, 10)]),
+            session(vec![player("Ann", 240, 200, 10), player("Cy", 0, 0, 0)]),
+        ];
+
+        let rows = leaderboard(&sessions);
+        let names = rows.iter().map(|row| row.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(vec!["Bob", "Ann", "Cy"], names);
+
+        let bob = &rows[0];
+        assert_eq!(2, bob.num_sessions);
+        assert_eq!(Some(Duration::from_secs(15)), bob.average_turn);
+        assert_eq!((0.25 + 0.25) / 2.0, bob.time_share);
+        assert_eq!(None, bob.opt_trend);
+
+        let ann = &rows[1];
+        assert_eq!(5, ann.num_sessions);
+        // 1700s over 45 turns.
+        assert_eq!(Some(Duration::from_millis(37_777)), ann.average_turn);
+        assert_eq!((0.75 + 0.5 + 0.75 + 1.0 + 1.0) / 5.0, ann.time_share);
+        // 800s over 30 turns, compared to 900s over 15 turns.
+        let trend = ann.opt_trend.unwrap();
+        assert!((trend - (800.0 / 30.0 / 60.0 - 1.0)).abs() < 0.001);
+    }
+}
@@ -1,38 +1,247 @@
+use crate::archive::ArchivedSession;
+use crate::layout::{Layout, PIE_RADIUS_CURRENT_TURN_MULTIPLIER};
+use crate::leaderboard::LeaderboardScreen;
+use crate::phases::{GamePhase, PhaseTimes, PHASE_OPTIONS};
+use crate::report::SessionReport;
+use crate::roster::RosterPlayer;
+use crate::rounds::RoundStats;
+use crate::session::SessionSnapshot;
+use crate::setup_screen::{SetupOutcome, SetupScreen};
+use crate::shared_turn::{SharedTimePolicy, SharedTurn};
+use crate::time_limits::{BankBalance, LimitStatus, TimeLimits};
+use crate::turn_log::{LoggedTurn, TurnChange, TurnLog};
+use better_quad::bq::{
+    self, BetterKeyCode, Gesture, GestureDetector, SimpleButton, SwipeDirection, TextAlignment,
+    TextAnchorPoint, TextBackground, Timestamp,
+};
 use better_quad::utils::infinite_iterator::InfiniteIterator;
-use better_quad::{bq::Timestamp, StatefulGui};
+use better_quad::StatefulGui;
 use macroquad::prelude as mq;
 use std::cmp::max;
 use std::collections::BinaryHeap;
 use std::time::Duration;
 
+mod archive;
+mod history_chart;
+mod launch_args;
+mod layout;
+mod leaderboard;
+mod phases;
+mod report;
+mod roster;
+mod rounds;
+mod session;
+mod setup_screen;
+mod shared_turn;
+mod time_limits;
+mod turn_log;
+
 // Control consts
 const KEY_NEXT_PLAYER: mq::KeyCode = mq::KeyCode::Space;
+const KEY_PREVIOUS_PLAYER: mq::KeyCode = mq::KeyCode::Left;
+const KEY_UNDO_PLAYER_CHANGE: mq::KeyCode = mq::KeyCode::Backspace;
+/// Jump to player N's turn. 0 is the 10th player.
+const KEYS_JUMP_TO_PLAYER: [mq::KeyCode; 10] = [
+    mq::KeyCode::Key1,
+    mq::KeyCode::Key2,
+    mq::KeyCode::Key3,
+    mq::KeyCode::Key4,
+    mq::KeyCode::Key5,
+    mq::KeyCode::Key6,
+    mq::KeyCode::Key7,
+    mq::KeyCode::Key8,
+    mq::KeyCode::Key9,
+    mq::KeyCode::Key0,
+];
 const KEY_PAUSE: mq::KeyCode = mq::KeyCode::P;
 const KEY_TIME_DISPLAY_TOGGLE: mq::KeyCode = mq::KeyCode::H;
 const KEY_DETAIL_MODE_TOGGLE: mq::KeyCode = mq::KeyCode::D;
+const KEY_VIEW_MODE_TOGGLE: mq::KeyCode = mq::KeyCode::C;
+const KEY_EXPORT_REPORT: mq::KeyCode = mq::KeyCode::E;
+/// Start or end a shared turn. While it lasts, jumping to a player adds or removes them instead.
+const KEY_SHARED_TURN_TOGGLE: mq::KeyCode = mq::KeyCode::S;
+/// Start a shared turn for the current player's team.
+const KEY_TEAM_TURN: mq::KeyCode = mq::KeyCode::T;
+const KEY_SHARED_TIME_POLICY_TOGGLE: mq::KeyCode = mq::KeyCode::X;
+/// Cycle through the game phases (setup, rules, ...) and back to the players' turns.
+const KEY_GAME_PHASE_CYCLE: mq::KeyCode = mq::KeyCode::G;
+/// Pressed twice, finish the game: archive it for the leaderboard and start over.
+const KEY_FINISH_GAME: mq::KeyCode = mq::KeyCode::F;
+/// Swipe left for the next player, right for the previous. Shorter swipes are taps.
+const MIN_SWIPE_DISTANCE: f32 = 80.0;
+const TOOLBAR_BUTTONS: [ToolbarButton; 4] = [
+    ToolbarButton::Pause,
+    ToolbarButton::TimeDisplay,
+    ToolbarButton::DetailMode,
+    ToolbarButton::FinishGame,
+];
 
-// Draw consts
-// TODO:3 dynamic size based on window
-const PIE_X: f32 = 300.0;
-const PIE_Y: f32 = 300.0;
-const PIE_THICKNESS: f32 = 230.0;
-const PIE_THICKNESS_CURRENT_TURN_MULTIPLIER: f32 = 1.2;
-
-const PAUSED_TEXT_FONT_SIZE: f32 = PLAYER_TEXT_FONT_SIZE;
-const PAUSED_TEXT_X: f32 = 10.0;
-const PAUSED_TEXT_Y: f32 = PIE_Y + PIE_THICKNESS;
-
-const PLAYER_TEXT_FONT_SIZE: f32 = 40.0;
-const PLAYER_TEXT_LINE_BUFFER: f32 = 10.0;
-const PLAYER_TEXT_X: f32 = 10.0;
-const PLAYER_TEXT_Y: f32 = PIE_THICKNESS + PIE_Y + 20.0;
+// Draw consts, sizes at full font size. See [layout] for positions.
 const PLAYER_RECTANGLE_THICKNESS: f32 = 6.0;
+const PLAYER_RECTANGLE_COLOR: mq::Color = mq::WHITE;
+const PLAYER_RECTANGLE_OVER_SOFT_LIMIT_COLOR: mq::Color = mq::ORANGE;
+const PLAYER_RECTANGLE_OVER_HARD_LIMIT_COLOR: mq::Color = mq::RED;
+/// Struck through the line of players who ran out of banked time.
+const FLAGGED_LINE_COLOR: mq::Color = mq::RED;
+const FLAGGED_LINE_THICKNESS: f32 = 4.0;
+/// Ring of team totals around the pie, relative to the pie radius.
+const TEAM_RING_GAP: f32 = 0.03;
+const TEAM_RING_THICKNESS: f32 = 0.08;
+
+/// Background alternates with this color every period once over the hard limit.
+const OVER_HARD_LIMIT_FLASH_COLOR: mq::Color = mq::RED;
+const OVER_HARD_LIMIT_FLASH_PERIOD: Duration = Duration::from_millis(500);
+
+const TOOLBAR_BUTTON_BORDER_THICKNESS: f32 = 2.0;
+/// Relative to the button height.
+const TOOLBAR_FONT_SIZE_FRACTION: f32 = 0.5;
+
+const STATUS_FONT_SIZE: u16 = 22;
+const STATUS_TEXT_PADDING: f32 = 4.0;
+const STATUS_DISPLAY_DURATION: Duration = Duration::from_secs(4);
+
+/// Setup screen to pick players, then the tracker itself. Finished games go to the leaderboard.
+pub struct TurnTimeTrackerApp {
+    screen: Screen,
+    // Offered on the setup screen until a session is started or resumed.
+    opt_saved_session: Option<SessionSnapshot>,
+    // For going back to the setup screen.
+    default_players: Vec<RosterPlayer>,
+}
+
+enum Screen {
+    Setup(SetupScreen),
+    Tracker(TurnTimeTracker),
+    Leaderboard(LeaderboardScreen),
+}
+
+impl TurnTimeTrackerApp {
+    /// `default_players` are offered on the setup screen if there's no roster saved from a
+    /// previous session. An autosaved session is offered to be resumed.
+    pub fn with_default_players<S: Into<String>>(default_players: Vec<(S, mq::Color)>) -> Self {
+        let default_players: Vec<RosterPlayer> = default_players
+            .into_iter()
+            .map(|(name, color)| RosterPlayer::new(name, color))
+            .collect();
+        let mut setup_screen = SetupScreen::new(default_players.clone());
+        let opt_saved_session = session::load_saved();
+        if let Some(saved_session) = &opt_saved_session {
+            setup_screen.set_resume_text(saved_session.summary());
+        }
+        Self {
+            screen: Screen::Setup(setup_screen),
+            opt_saved_session,
+            default_players,
+        }
+    }
+
+    /// Skips the setup screen if players were given at launch (see the README). Invalid launch
+    /// players are reported on the setup screen.
+    pub fn from_launch_args<S: Into<String>>(default_players: Vec<(S, mq::Color)>) -> Self {
+        let mut app = Self::with_default_players(default_players);
+        match launch_args::roster_from_launch_args() {
+            Ok(Some(players)) => {
+                // Use the limits last picked on the setup screen.
+                app.screen = Screen::Tracker(
+                    TurnTimeTracker::from_roster(players)
+                        .with_time_limits(time_limits::load_saved())
+                        .with_game_name(archive::load_saved_game_name()),
+                );
+            }
+            Ok(None) => {}
+            Err(e) => {
+                mq::error!("Invalid launch players: {e}");
+                if let Screen::Setup(setup_screen) = &mut app.screen {
+                    setup_screen.set_error_text(format!("Invalid launch players: {e}"));
+                }
+            }
+        }
+        app
+    }
+}
+
+impl StatefulGui for TurnTimeTrackerApp {
+    fn main_conf() -> mq::Conf {
+        TurnTimeTracker::main_conf()
+    }
+
+    fn update(&mut self, now: Timestamp) {
+        match &mut self.screen {
+            Screen::Setup(setup_screen) => match setup_screen.update() {
+                SetupOutcome::Editing => {}
+                SetupOutcome::Start(players, time_limits, game_name) => {
+                    // The new session's autosaves replace it.
+                    self.opt_saved_session = None;
+                    self.screen = Screen::Tracker(
+                        TurnTimeTracker::from_roster(players)
+                            .with_time_limits(time_limits)
+                            .with_game_name(game_name),
+                    );
+                }
+                SetupOutcome::Resume => {
+                    if let Some(saved_session) = self.opt_saved_session.take() {
+                        self.screen = Screen::Tracker(saved_session.into_tracker(now));
+                    }
+                }
+                SetupOutcome::ShowLeaderboard => {
+                    self.screen = Screen::Leaderboard(LeaderboardScreen::new(archive::load()));
+                }
+            },
+            Screen::Tracker(tracker) => {
+                tracker.update(now);
+                if tracker.is_finished() {
+                    self.screen = Screen::Leaderboard(LeaderboardScreen::new(archive::load()));
+                }
+            }
+            Screen::Leaderboard(leaderboard_screen) => {
+                if leaderboard_screen.update() {
+                    let mut setup_screen = SetupScreen::new(self.default_players.clone());
+                    // Whatever's saved now, not what was saved at launch.
+                    self.opt_saved_session = session::load_saved();
+                    if let Some(saved_session) = &self.opt_saved_session {
+                        setup_screen.set_resume_text(saved_session.summary());
+                    }
+                    self.screen = Screen::Setup(setup_screen);
+                }
+            }
+        }
+    }
+
+    fn draw(&self) {
+        match &self.screen {
+            Screen::Setup(setup_screen) => setup_screen.draw(),
+            Screen::Tracker(tracker) => tracker.draw(),
+            Screen::Leaderboard(leaderboard_screen) => leaderboard_screen.draw(),
+        }
+    }
+}
 
 pub struct TurnTimeTracker {
+    /// Empty if not given.
+    game_name: String,
     players: InfiniteIterator<Player>,
+    turn_log: TurnLog,
     timer: TimerState,
+    time_limits: TimeLimits,
     time_display_mode: TimeDisplayMode,
     text_detail_mode: TextDetailMode,
+    view_mode: ViewMode,
+    opt_shared_turn: Option<SharedTurn>,
+    shared_time_policy: SharedTimePolicy,
+    /// While in a phase, time goes to it instead of any player.
+    opt_phase: Option<GamePhase>,
+    phase_times: PhaseTimes,
+    /// Parallel to [TOOLBAR_BUTTONS].
+    toolbar_buttons: [SimpleButton; TOOLBAR_BUTTONS.len()],
+    gesture_detector: GestureDetector,
+    opt_last_autosave: Option<Timestamp>,
+    // Set by changes that should be saved right away rather than on the next autosave period.
+    autosave_pending: bool,
+    status_text: Option<(String, Timestamp)>,
+    // When finishing the game was first pressed, to be confirmed by pressing again.
+    opt_finish_prompt: Option<Timestamp>,
+    // Archived, so nothing is saved anymore.
+    finished: bool,
 }
 
 #[derive(Copy, Clone)]
@@ -41,6 +250,15 @@ enum TimerState {
     Running { last_tick: Timestamp },
 }
 
+/// Which way around the table a player change goes, to know if it crosses into another round.
+#[derive(Copy, Clone)]
+enum TurnDirection {
+    /// Next player, or jumping ahead to a player. Wrapping past the last player starts a round.
+    Forward,
+    /// Previous player. Wrapping back past the first player goes back a round.
+    Backward,
+}
+
 #[derive(Copy, Clone)]
 enum TextDetailMode {
     Concise,
@@ -53,12 +271,27 @@ enum TimeDisplayMode {
     Hidden,
 }
 
+/// On-screen buttons along the bottom, doing the same as their keys, for touch screens.
+#[derive(Copy, Clone, PartialEq)]
+enum ToolbarButton {
+    Pause,
+    TimeDisplay,
+    DetailMode,
+    FinishGame,
+}
+
+/// Only applies while times are shown.
+#[derive(Copy, Clone)]
+enum ViewMode {
+    PieAndPlayers,
+    HistoryChart,
+}
+
 impl StatefulGui for TurnTimeTracker {
     fn main_conf() -> mq::Conf {
         mq::Conf {
             window_title: "Tabletop Turn Time Tracker".to_string(),
-            window_width: (PIE_X + PIE_THICKNESS * PIE_THICKNESS_CURRENT_TURN_MULTIPLIER + 20.0)
-                as i32,
+            window_width: 600,
             window_height: 1000,
             ..Default::default()
         }
@@ -66,6 +299,9 @@ impl StatefulGui for TurnTimeTracker {
 
     fn update(&mut self, now: Timestamp) {
         self.evaluate_state(now);
+        if !self.finished {
+            self.autosave_if_due(now);
+        }
     }
 
     fn draw(&self) {
@@ -74,23 +310,91 @@ impl StatefulGui for TurnTimeTracker {
 }
 
 impl TurnTimeTracker {
-    /// Constructor to make tracker from static player list.
-    pub fn with_players(players: Vec<(&'static str, mq::Color)>) -> Self {
-        let players = players
+    /// Constructor to make tracker from a fixed player list, skipping the setup screen.
+    pub fn with_players<S: Into<String>>(players: Vec<(S, mq::Color)>) -> Self {
+        Self::from_roster(
+            players
+                .into_iter()
+                .map(|(player_name, player_color)| RosterPlayer::new(player_name, player_color))
+                .collect(),
+        )
+    }
+
+    pub(crate) fn from_roster(roster_players: Vec<RosterPlayer>) -> Self {
+        let players = roster_players
             .into_iter()
-            .map(|(player_name, player_color)| Player::new(player_name, player_color))
+            .map(|roster_player| {
+                let mut player = Player::new(roster_player.name, roster_player.color);
+                player.team = roster_player.team;
+                player
+            })
             .collect::<Vec<_>>();
+        Self::from_state(
+            InfiniteIterator::from(players),
+            TurnLog::new(),
+            TimerState::Paused,
+        )
+    }
+
+    fn from_state(players: InfiniteIterator<Player>, turn_log: TurnLog, timer: TimerState) -> Self {
         Self {
-            players: InfiniteIterator::from(players),
-            timer: TimerState::Paused,
+            game_name: String::new(),
+            players,
+            turn_log,
+            timer,
+            time_limits: TimeLimits::default(),
             time_display_mode: TimeDisplayMode::Shown,
             text_detail_mode: TextDetailMode::Concise,
+            view_mode: ViewMode::PieAndPlayers,
+            opt_shared_turn: None,
+            shared_time_policy: SharedTimePolicy::default(),
+            opt_phase: None,
+            phase_times: PhaseTimes::default(),
+            // Positioned by the layout each frame.
+            toolbar_buttons: TOOLBAR_BUTTONS.map(|_| SimpleButton::new(0.0, 0.0, 0.0, 0.0)),
+            gesture_detector: GestureDetector::new(MIN_SWIPE_DISTANCE),
+            opt_last_autosave: None,
+            autosave_pending: false,
+            status_text: None,
+            opt_finish_prompt: None,
+            finished: false,
         }
     }
 
+    pub(crate) fn with_time_limits(mut self, time_limits: TimeLimits) -> Self {
+        self.time_limits = time_limits;
+        self
+    }
+
+    pub(crate) fn with_game_name(mut self, game_name: String) -> Self {
+        self.game_name = game_name;
+        self
+    }
+
+    /// Whether the game was finished and archived. The app then moves on to the leaderboard.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
     fn evaluate_state(&mut self, now: Timestamp) {
+        let layout = self.layout(&self.text_lines(&self.round_stats()));
+        let opt_toolbar_button = self.tick_toolbar(&layout);
+        let opt_gesture = self.gesture_detector.tick();
+        let toolbar_pressed = |button| opt_toolbar_button == Some(button);
+        let pause_pressed = mq::is_key_pressed(KEY_PAUSE) || toolbar_pressed(ToolbarButton::Pause);
+        // The pie area is a big tap target to pass the turn.
+        let pie_tapped = matches!(opt_gesture, Some(Gesture::Tap(position))
+            if layout.pie_area.contains(position));
+        let next_pressed = mq::is_key_pressed(KEY_NEXT_PLAYER)
+            || pie_tapped
+            || opt_gesture == Some(Gesture::Swipe(SwipeDirection::Left));
+        let previous_pressed = mq::is_key_pressed(KEY_PREVIOUS_PLAYER)
+            || opt_gesture == Some(Gesture::Swipe(SwipeDirection::Right));
+
         // Toggle time display if needed
-        if mq::is_key_pressed(KEY_TIME_DISPLAY_TOGGLE) {
+        if mq::is_key_pressed(KEY_TIME_DISPLAY_TOGGLE)
+            || toolbar_pressed(ToolbarButton::TimeDisplay)
+        {
             self.time_display_mode = match self.time_display_mode {
                 TimeDisplayMode::Shown => TimeDisplayMode::Hidden,
                 TimeDisplayMode::Hidden => TimeDisplayMode::Shown,
@@ -98,24 +402,92 @@ impl TurnTimeTracker {
         }
 
         // Toggle detail mode if needed
-        if mq::is_key_pressed(KEY_DETAIL_MODE_TOGGLE) {
+        if mq::is_key_pressed(KEY_DETAIL_MODE_TOGGLE) || toolbar_pressed(ToolbarButton::DetailMode)
+        {
             self.text_detail_mode = match self.text_detail_mode {
                 TextDetailMode::Concise => TextDetailMode::Detailed,
                 TextDetailMode::Detailed => TextDetailMode::Concise,
             };
         }
 
+        // Toggle chart view if needed
+        if mq::is_key_pressed(KEY_VIEW_MODE_TOGGLE) {
+            self.view_mode = match self.view_mode {
+                ViewMode::PieAndPlayers => ViewMode::HistoryChart,
+                ViewMode::HistoryChart => ViewMode::PieAndPlayers,
+            };
+        }
+
+        if mq::is_key_pressed(KEY_EXPORT_REPORT) {
+            self.export_report(now);
+        }
+        if self
+            .status_text
+            .as_ref()
+            .is_some_and(|(_, set_time)| now - *set_time > STATUS_DISPLAY_DURATION)
+        {
+            self.status_text = None;
+        }
+
+        if mq::is_key_pressed(KEY_SHARED_TURN_TOGGLE) {
+            self.toggle_shared_turn();
+        }
+        if mq::is_key_pressed(KEY_TEAM_TURN) {
+            self.start_team_turn(now);
+        }
+        if mq::is_key_pressed(KEY_SHARED_TIME_POLICY_TOGGLE) {
+            self.shared_time_policy = self.shared_time_policy.toggled();
+            self.autosave_pending = true;
+        }
+        if mq::is_key_pressed(KEY_GAME_PHASE_CYCLE) {
+            self.opt_phase = next_option(&PHASE_OPTIONS, self.opt_phase);
+            self.autosave_pending = true;
+        }
+
+        if mq::is_key_pressed(KEY_FINISH_GAME) || toolbar_pressed(ToolbarButton::FinishGame) {
+            let is_confirmed = self
+                .opt_finish_prompt
+                .is_some_and(|prompt_time| now - prompt_time <= STATUS_DISPLAY_DURATION);
+            if is_confirmed {
+                self.finish_game(now);
+            } else {
+                self.opt_finish_prompt = Some(now);
+                self.status_text = Some((
+                    format!(
+                        "Press [{}] or Finish again to end the game and archive it",
+                        KEY_FINISH_GAME.to_lowercase()
+                    ),
+                    now,
+                ));
+            }
+        }
+
+        // Undo works while paused too, in case the mistake is noticed after pausing.
+        if mq::is_key_pressed(KEY_UNDO_PLAYER_CHANGE) {
+            self.undo_player_change();
+            self.autosave_pending = true;
+        }
+
         match &mut self.timer {
             TimerState::Paused => {
                 // Check for unpause
-                if mq::is_key_pressed(KEY_PAUSE) {
+                if pause_pressed {
                     self.timer = TimerState::Running { last_tick: now };
+                    self.autosave_pending = true;
+                }
+
+                // Allow picking who goes (first) while paused, but don't allow stepping through
+                // players with next/previous, so an accidental press during a break is harmless.
+                if let Some(jump_index) = self.get_jump_to_player_input(&layout, opt_gesture) {
+                    self.end_phase();
+                    self.jump_to_player(jump_index);
                 }
             }
             TimerState::Running { ref mut last_tick } => {
                 // Check for pause
-                if mq::is_key_pressed(KEY_PAUSE) {
+                if pause_pressed {
                     self.timer = TimerState::Paused;
+                    self.autosave_pending = true;
                     return;
                 }
 
@@ -123,164 +495,749 @@ impl TurnTimeTracker {
                 let elapsed_tick_time = now
                     .duration_since(*last_tick)
                     .expect("Elapsed tick time underflow");
-                self.players.current_mut().tick_frame(elapsed_tick_time);
-
                 *last_tick = now;
+                self.tick_current_player(elapsed_tick_time);
 
                 // Change current player if needed. Do this AFTER ticking current player so previous
                 // player is attributed the time until we process the player change.
-                if mq::is_key_pressed(KEY_NEXT_PLAYER) {
-                    self.players.current_mut().stats.end_turn();
-                    self.players.advance();
+                // Next/previous also end any shared turn. During a phase, they only end the phase,
+                // going back to the turn it interrupted.
+                let (players, current_index) = self.players.raw();
+                let num_players = players.len();
+                if self.opt_phase.is_some() && (next_pressed || previous_pressed) {
+                    self.end_phase();
+                } else if next_pressed {
+                    self.end_shared_turn();
+                    self.change_player(self.next_player_index(), TurnDirection::Forward);
+                } else if previous_pressed {
+                    self.end_shared_turn();
+                    self.change_player(
+                        (current_index + num_players - 1) % num_players,
+                        TurnDirection::Backward,
+                    );
+                } else if let Some(jump_index) = self.get_jump_to_player_input(&layout, opt_gesture)
+                {
+                    self.end_phase();
+                    self.jump_to_player(jump_index);
                 }
+            }
+        }
+    }
 
-                // TODO:2 press 1-9 to fastswap to player turn
+    /// Tick the current phase, the current player, or the active players of a shared turn.
+    fn tick_current_player(&mut self, elapsed_tick_time: Duration) {
+        if let Some(phase) = self.opt_phase {
+            self.phase_times.tick(phase, elapsed_tick_time);
+            return;
+        }
+        match &self.opt_shared_turn {
+            None => self.players.current_mut().tick_frame(elapsed_tick_time),
+            Some(shared_turn) => {
+                let share = shared_turn.share(elapsed_tick_time, self.shared_time_policy);
+                for i in shared_turn.active_indices() {
+                    let player = self.players.get_mut(*i);
+                    player.total_time += share;
+                    player.shared_time += share;
+                }
             }
         }
+        self.record_flags();
     }
 
-    fn draw_state(&self) {
-        let bg_color = match self.timer {
-            TimerState::Paused => mq::DARKGRAY,
-            TimerState::Running { .. } => mq::LIGHTGRAY,
+    /// Flag players the moment their bank runs out, so they stay flagged.
+    fn record_flags(&mut self) {
+        let newly_flagged = self
+            .players
+            .raw()
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(_, player)| {
+                !player.flagged
+                    && matches!(self.bank_balance(player), Some(BankBalance::Flagged { .. }))
+            })
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        for i in newly_flagged {
+            self.players.get_mut(i).flagged = true;
+            self.autosave_pending = true;
+        }
+    }
+
+    /// Start a shared turn with just the current player active, or end the shared turn.
+    fn toggle_shared_turn(&mut self) {
+        if self.opt_shared_turn.is_some() {
+            self.end_shared_turn();
+        } else {
+            self.opt_shared_turn = Some(SharedTurn::new(vec![self.players.raw().1]));
+            self.autosave_pending = true;
+        }
+    }
+
+    /// Start a shared turn with the current player's team active.
+    fn start_team_turn(&mut self, now: Timestamp) {
+        let (players, current_index) = self.players.raw();
+        let Some(team) = shared_turn::teams(players)
+            .into_iter()
+            .find(|team| team.member_indices.contains(&current_index))
+        else {
+            self.status_text = Some((
+                format!("{} isn't on a team", players[current_index].display_name),
+                now,
+            ));
+            return;
         };
-        mq::clear_background(bg_color);
-        let (players, current_player_index) = self.players.raw();
+        self.opt_shared_turn = Some(SharedTurn::new(team.member_indices));
+        self.autosave_pending = true;
+    }
+
+    /// Back to the players' turns.
+    fn end_phase(&mut self) {
+        if self.opt_phase.take().is_some() {
+            self.autosave_pending = true;
+        }
+    }
 
-        let mut all_total_time = Duration::ZERO;
-        for player in players {
-            all_total_time += player.total_time
+    /// Back to the current player's turn, which was on hold during the shared turn.
+    fn end_shared_turn(&mut self) {
+        if self.opt_shared_turn.take().is_some() {
+            self.autosave_pending = true;
         }
+    }
 
-        match self.time_display_mode {
-            TimeDisplayMode::Shown => Self::draw_pie(players, current_player_index, all_total_time),
-            TimeDisplayMode::Hidden => {}
+    /// Make the player current, or add/remove them from the active players of a shared turn.
+    fn jump_to_player(&mut self, player_index: usize) {
+        match &mut self.opt_shared_turn {
+            Some(shared_turn) => {
+                shared_turn.toggle(player_index);
+                self.autosave_pending = true;
+            }
+            None => self.change_player(player_index, TurnDirection::Forward),
         }
-        self.draw_player_text(players, current_player_index, all_total_time);
+    }
+
+    /// Whether the player is taking a turn now, on their own or as part of a shared turn. Nobody
+    /// is during a phase.
+    fn is_active(&self, player_index: usize) -> bool {
+        match (self.opt_phase, &self.opt_shared_turn) {
+            (Some(_), _) => false,
+            (None, Some(shared_turn)) => shared_turn.is_active(player_index),
+            (None, None) => player_index == self.players.raw().1,
+        }
+    }
+
+    /// The player after the current one, passing over flagged players if set to skip them. If
+    /// everyone else is flagged, the current player keeps going.
+    fn next_player_index(&self) -> usize {
+        let (players, current_index) = self.players.raw();
+        let num_players = players.len();
+        (1..=num_players)
+            .map(|offset| (current_index + offset) % num_players)
+            .find(|i| !self.time_limits.skip_flagged_players || !self.is_flagged(&players[*i]))
+            .unwrap_or((current_index + 1) % num_players)
+    }
+
+    /// Move the toolbar buttons to where the layout puts them, and return the one clicked, if any.
+    fn tick_toolbar(&mut self, layout: &Layout) -> Option<ToolbarButton> {
+        let mut opt_clicked = None;
+        for (i, (button, toolbar_button)) in self
+            .toolbar_buttons
+            .iter_mut()
+            .zip(TOOLBAR_BUTTONS)
+            .enumerate()
+        {
+            let bounds = layout.toolbar_button(i, TOOLBAR_BUTTONS.len());
+            button.set_bounds(bounds.x, bounds.y, bounds.w, bounds.h);
+            // Unconditionally tick all buttons so their press state stays in sync with the mouse.
+            if button.tick_state().should_trigger_action() {
+                opt_clicked = Some(toolbar_button);
+            }
+        }
+        opt_clicked
+    }
+
+    /// Player index from a number key press or tap (or click) on a player's row.
+    fn get_jump_to_player_input(
+        &self,
+        layout: &Layout,
+        opt_gesture: Option<Gesture>,
+    ) -> Option<usize> {
+        let num_players = self.players.raw().0.len();
+
+        let opt_key_index = KEYS_JUMP_TO_PLAYER
+            .iter()
+            .position(|key| mq::is_key_pressed(*key));
+        // Player rows are only tappable when they're drawn.
+        let opt_tap_index = match opt_gesture {
+            Some(Gesture::Tap(position)) if !self.is_showing_history_chart() => {
+                let line_gap = layout.line_height() - layout.text_font_size;
+                (0..num_players).find(|i| {
+                    let row_bottom = layout.text_line_y(*i) + line_gap / 2.0;
+                    let row_top = row_bottom - layout.line_height();
+                    (row_top..row_bottom).contains(&position.y)
+                        && position.x >= layout.text_x - line_gap
+                })
+            }
+            _ => None,
+        };
+
+        opt_key_index
+            .or(opt_tap_index)
+            .filter(|index| *index < num_players)
+    }
+
+    /// End the current player's turn and make `new_index` the current player.
+    fn change_player(&mut self, new_index: usize, direction: TurnDirection) {
+        let from_index = self.players.raw().1;
+        if new_index == from_index {
+            return;
+        }
+        let current_round = self.turn_log.current_round();
+        let to_round = match direction {
+            TurnDirection::Forward if new_index < from_index => current_round + 1,
+            TurnDirection::Backward if new_index > from_index => max(current_round - 1, 1),
+            _ => current_round,
+        };
+
+        let ended_turn = self.players.current_mut().stats.end_turn();
+        self.players.set_current_index(new_index);
+        self.turn_log.record(TurnChange {
+            from_index,
+            to_index: new_index,
+            to_round,
+            ended_turn,
+        });
+        self.autosave_pending = true;
+    }
+
+    /// Export the session so far as CSV and JSON report files. On the web, only the JSON, since
+    /// browsers block several downloads at once, and it has everything the CSVs do.
+    fn export_report(&mut self, now: Timestamp) {
+        let report = SessionReport::new(self);
+        let exports = if cfg!(target_arch = "wasm32") {
+            vec![(report::JSON_FILE, report.to_json())]
+        } else {
+            vec![
+                (report::TURNS_CSV_FILE, report.turns_csv()),
+                (report::PLAYERS_CSV_FILE, report.players_csv()),
+                (report::PHASES_CSV_FILE, report.phases_csv()),
+                (report::JSON_FILE, report.to_json()),
+            ]
+        };
+        let mut status_lines = Vec::new();
+        for (file_name, contents) in exports {
+            match bq::export_file(file_name, &contents) {
+                Ok(description) => status_lines.push(description),
+                Err(e) => {
+                    mq::error!("Failed to export report: {e}");
+                    status_lines.push(format!("Failed to export: {e}"));
+                }
+            }
+        }
+        self.status_text = Some((status_lines.join("\n"), now));
+    }
+
+    /// Archive the session for the leaderboard, and drop the autosave so it's not offered to
+    /// resume.
+    fn finish_game(&mut self, now: Timestamp) {
+        match archive::add_session(ArchivedSession::capture(self, now)) {
+            Ok(()) => {
+                session::clear_saved();
+                self.finished = true;
+            }
+            Err(e) => {
+                mq::error!("Failed to archive session: {e}");
+                self.status_text = Some((format!("Failed to archive: {e}"), now));
+            }
+        }
+    }
+
+    /// Save immediately after a change, or periodically while the timer runs. Nothing changes while
+    /// paused, so there's nothing to save then.
+    fn autosave_if_due(&mut self, now: Timestamp) {
+        let period_elapsed = self
+            .opt_last_autosave
+            .is_none_or(|last_autosave| now - last_autosave >= session::AUTOSAVE_PERIOD);
+        let is_running = matches!(self.timer, TimerState::Running { .. });
+        if self.autosave_pending || (is_running && period_elapsed) {
+            session::save(self);
+            self.opt_last_autosave = Some(now);
+            self.autosave_pending = false;
+        }
+    }
+
+    /// Roll back the most recent player change, as if it never happened: the time since the change
+    /// is given back to the previous player, whose turn continues.
+    fn undo_player_change(&mut self) {
+        let Some(change) = self.turn_log.pop() else {
+            return;
+        };
+        debug_assert_eq!(change.to_index, self.players.raw().1);
+
+        let mistaken_player = self.players.current_mut();
+        let misattributed_time = mistaken_player.stats.take_current_turn();
+        mistaken_player.total_time -= misattributed_time;
+
+        self.players.set_current_index(change.from_index);
+        let resumed_player = self.players.current_mut();
+        resumed_player
+            .stats
+            .resume_ended_turn(change.ended_turn, misattributed_time);
+        resumed_player.total_time += misattributed_time;
+    }
+
+    /// Flagged players stay flagged, over by however long they've been taking their turn.
+    fn bank_balance(&self, player: &Player) -> Option<BankBalance> {
+        let balance = self.time_limits.bank_balance(
+            player.total_time,
+            player.stats.completed_turn_durations.iter().copied(),
+            player.stats.current_turn_duration,
+        )?;
+        Some(match balance {
+            BankBalance::Remaining(_) if player.flagged => BankBalance::Flagged {
+                over_by: player.stats.current_turn_duration,
+            },
+            balance => balance,
+        })
+    }
+
+    fn is_flagged(&self, player: &Player) -> bool {
+        matches!(self.bank_balance(player), Some(BankBalance::Flagged { .. }))
+    }
+
+    /// Turn limits don't apply to shared turns or phases.
+    fn current_limit_status(&self) -> LimitStatus {
+        if self.opt_shared_turn.is_some() || self.opt_phase.is_some() {
+            return LimitStatus::WithinLimits;
+        }
+        let current_player = self.players.current();
+        self.time_limits.status(
+            current_player.stats.current_turn_duration,
+            self.bank_balance(current_player),
+        )
+    }
+
+    /// Total time, or the time left in the player's bank if playing with time banks.
+    fn displayed_total_time(&self, player: &Player) -> Duration {
+        match self.bank_balance(player) {
+            Some(BankBalance::Remaining(remaining)) => remaining,
+            Some(BankBalance::Flagged { .. }) => Duration::ZERO,
+            None => player.total_time,
+        }
+    }
+
+    fn draw_state(&self) {
+        let limit_status = self.current_limit_status();
+        let bg_color = match (self.timer, limit_status) {
+            (TimerState::Paused, _) => mq::DARKGRAY,
+            (TimerState::Running { .. }, LimitStatus::OverHardLimit { over_by })
+                if (over_by.as_millis() / OVER_HARD_LIMIT_FLASH_PERIOD.as_millis())
+                    .is_multiple_of(2) =>
+            {
+                OVER_HARD_LIMIT_FLASH_COLOR
+            }
+            (TimerState::Running { .. }, _) => mq::LIGHTGRAY,
+        };
+        mq::clear_background(bg_color);
+        let players = self.players.raw().0;
+
+        let text_lines = self.text_lines(&self.round_stats());
+        let layout = self.layout(&text_lines);
+        if self.is_showing_history_chart() {
+            history_chart::draw_history_chart(players, &self.turns());
+        } else {
+            if let TimeDisplayMode::Shown = self.time_display_mode {
+                self.draw_pie(&layout, players);
+            }
+            self.draw_player_text(&layout, &text_lines, players);
+        }
+        self.draw_round_header(&layout);
+        self.draw_toolbar(&layout);
 
         if let TimerState::Paused = self.timer {
             mq::draw_text(
                 "PAUSED",
-                PAUSED_TEXT_X,
-                PAUSED_TEXT_Y,
-                PAUSED_TEXT_FONT_SIZE,
+                layout.corner_text_x(),
+                layout.paused_text_y(),
+                layout.header_font_size,
+                mq::WHITE,
+            );
+        }
+
+        if let Some((status_text, _)) = &self.status_text {
+            bq::draw_text(
+                status_text,
+                TextAlignment::Left,
+                None,
+                STATUS_FONT_SIZE,
                 mq::WHITE,
+                // Above the toolbar, so it doesn't cover the buttons.
+                TextAnchorPoint::BottomLeft {
+                    x: 0.0,
+                    y: layout.toolbar_top(),
+                },
+                Some(TextBackground {
+                    color: mq::BLACK,
+                    x_padding: STATUS_TEXT_PADDING,
+                    y_padding: STATUS_TEXT_PADDING,
+                }),
             );
         }
     }
 
-    fn draw_player_text(
-        &self,
-        players: &[Player],
-        current_player_index: usize,
-        all_total_time: Duration,
-    ) {
-        for (i, player) in players.iter().enumerate() {
-            let text_line_name = format!(
-                // Names longer than 8 chars will push the line out a little bit :P oh well
-                "{} {: <8}",
-                if i == current_player_index {
+    fn draw_toolbar(&self, layout: &Layout) {
+        let font_size = (TOOLBAR_FONT_SIZE_FRACTION * layout.toolbar_button(0, 1).h) as u16;
+        for (button, toolbar_button) in self.toolbar_buttons.iter().zip(TOOLBAR_BUTTONS) {
+            // Labeled with what the button will do.
+            let text = match toolbar_button {
+                ToolbarButton::Pause => match self.timer {
+                    TimerState::Paused => "Resume",
+                    TimerState::Running { .. } => "Pause",
+                },
+                ToolbarButton::TimeDisplay => match self.time_display_mode {
+                    TimeDisplayMode::Shown => "Hide",
+                    TimeDisplayMode::Hidden => "Show",
+                },
+                ToolbarButton::DetailMode => match self.text_detail_mode {
+                    TextDetailMode::Concise => "Details",
+                    TextDetailMode::Detailed => "Concise",
+                },
+                ToolbarButton::FinishGame => "Finish",
+            };
+            let btn_color = if button.is_pressed() {
+                mq::GRAY
+            } else {
+                mq::WHITE
+            };
+            button.draw(
+                btn_color,
+                mq::BLACK,
+                TOOLBAR_BUTTON_BORDER_THICKNESS,
+                text,
+                font_size,
+                mq::BLACK,
+            );
+        }
+    }
+
+    fn is_showing_history_chart(&self) -> bool {
+        matches!(
+            (self.time_display_mode, self.view_mode),
+            (TimeDisplayMode::Shown, ViewMode::HistoryChart)
+        )
+    }
+
+    /// Every turn so far in order, including the current turn.
+    fn turns(&self) -> Vec<LoggedTurn> {
+        let (players, current_index) = self.players.raw();
+        self.turn_log.turns(
+            current_index,
+            players[current_index].stats.current_turn_duration,
+        )
+    }
+
+    fn round_stats(&self) -> RoundStats {
+        RoundStats::new(
+            &self.turns(),
+            self.players.raw().0.len(),
+            self.turn_log.current_round(),
+        )
+    }
+
+    /// Layout for the current window size, with the font shrunk to fit the widest of `text_lines`.
+    fn layout(&self, text_lines: &[(String, mq::Color)]) -> Layout {
+        let widest_line_width = text_lines
+            .iter()
+            .map(|(line, _)| mq::measure_text(line, None, layout::MAX_FONT_SIZE as u16, 1.0).width)
+            .fold(0.0, f32::max);
+        // Room for team totals and the round summary is always kept, so rows don't jump around
+        // when they appear.
+        let players = self.players.raw().0;
+        Layout::new(
+            mq::screen_width(),
+            mq::screen_height(),
+            players.len() + shared_turn::teams(players).len() + self.shown_phases().count() + 1,
+            widest_line_width,
+        )
+    }
+
+    fn draw_round_header(&self, layout: &Layout) {
+        let current_round = self.turn_log.current_round();
+        let shared_turn_text = match (self.opt_phase, &self.opt_shared_turn) {
+            (Some(phase), _) => format!(" -- {}", phase.label()),
+            (None, Some(_)) => format!(" -- shared turn, time {}", self.shared_time_policy.label()),
+            (None, None) => "".to_string(),
+        };
+        mq::draw_text(
+            format!("Round {current_round}{shared_turn_text}"),
+            layout.corner_text_x(),
+            layout.header_y(),
+            layout.header_font_size,
+            mq::BLACK,
+        );
+    }
+
+    /// Phases with any time so far, and the current phase.
+    fn shown_phases(&self) -> impl Iterator<Item = (GamePhase, Duration)> + '_ {
+        self.phase_times
+            .iter()
+            .filter(|(phase, time)| !time.is_zero() || self.opt_phase == Some(*phase))
+    }
+
+    /// A line per player, followed by team and phase totals when times are shown, and a summary of
+    /// past rounds in detailed mode. Each with the color to draw it in.
+    fn text_lines(&self, round_stats: &RoundStats) -> Vec<(String, mq::Color)> {
+        let (players, current_player_index) = self.players.raw();
+        let all_total_time = all_total_time(players);
+        let mut lines = players
+            .iter()
+            .enumerate()
+            .map(|(i, player)| {
+                let line = self.player_text_line(
+                    i,
+                    player,
+                    current_player_index,
+                    all_total_time,
+                    round_stats,
+                );
+                (line, player.display_color)
+            })
+            .collect::<Vec<_>>();
+        if let TimeDisplayMode::Shown = self.time_display_mode {
+            for team in shared_turn::teams(players) {
+                let team_total_time = team.total_time(players);
+                let line = format!(
+                    "{}: {} ({: >2.0}%)",
+                    team.name,
+                    format_duration_concise(team_total_time),
+                    100.0 * (team_total_time.as_secs_f32() / all_total_time.as_secs_f32()),
+                );
+                lines.push((line, team.color));
+            }
+            for (phase, time) in self.shown_phases() {
+                let marker = if self.opt_phase == Some(phase) {
                     "[X]"
                 } else {
                     "[ ]"
-                },
-                player.display_name
-            );
+                };
+                let line = format!(
+                    "{marker} {}: {}",
+                    phase.label(),
+                    format_duration_concise(time)
+                );
+                lines.push((line, phase.color()));
+            }
+        }
+        if let Some(summary) = self.round_summary_line(round_stats) {
+            lines.push((summary, mq::BLACK));
+        }
+        lines
+    }
 
-            let text_line_info = match (self.time_display_mode, self.text_detail_mode) {
-                (TimeDisplayMode::Hidden, _) => {
-                    if i == current_player_index {
-                        format_duration_concise(player.stats.current_turn_duration)
-                    } else {
-                        "".to_string()
-                    }
+    /// Summary of past rounds below the player list, in detailed mode.
+    fn round_summary_line(&self, round_stats: &RoundStats) -> Option<String> {
+        let (TimeDisplayMode::Shown, TextDetailMode::Detailed) =
+            (self.time_display_mode, self.text_detail_mode)
+        else {
+            return None;
+        };
+        let (Some((last_round, last_duration)), Some((slowest_round, slowest_duration))) = (
+            round_stats.last_completed_round(),
+            round_stats.slowest_completed_round(),
+        ) else {
+            return None;
+        };
+        Some(format!(
+            "Rounds -- last (#{last_round}): {}, slowest (#{slowest_round}): {}",
+            format_duration_concise(last_duration),
+            format_duration_concise(slowest_duration)
+        ))
+    }
+
+    fn player_text_line(
+        &self,
+        i: usize,
+        player: &Player,
+        current_player_index: usize,
+        all_total_time: Duration,
+        round_stats: &RoundStats,
+    ) -> String {
+        let text_line_name = format!(
+            // Names longer than 8 chars will push the line out a little bit :P oh well
+            "{} {: <8}",
+            if self.is_active(i) { "[X]" } else { "[ ]" },
+            player.display_name
+        );
+
+        let text_line_info = match (self.time_display_mode, self.text_detail_mode) {
+            (TimeDisplayMode::Hidden, _) => {
+                if i == current_player_index && self.opt_shared_turn.is_none() {
+                    format_duration_concise(player.stats.current_turn_duration)
+                } else {
+                    "".to_string()
                 }
-                (TimeDisplayMode::Shown, TextDetailMode::Concise) => format!(
-                    "{} ({: >2.0}%)",
-                    format_duration_concise(player.total_time),
-                    100.0 * (player.total_time.as_secs_f32() / all_total_time.as_secs_f32()),
-                ),
-                (TimeDisplayMode::Shown, TextDetailMode::Detailed) => format!(
-                    "{} ({: >2.0}%) -- ({} turns; avg: {}, max: {}, median: {})",
-                    format_duration_detailed(player.total_time),
-                    100.0 * (player.total_time.as_secs_f32() / all_total_time.as_secs_f32()),
-                    player.stats.num_turns(),
-                    format_duration_stats(if player.stats.num_turns() == 0 {
-                        None
-                    } else {
-                        Some(player.total_time / player.stats.num_turns() as u32)
-                    }),
-                    format_duration_stats(player.stats.max_turn()),
-                    format_duration_stats(player.stats.median_turn())
-                ),
-            };
+            }
+            (TimeDisplayMode::Shown, TextDetailMode::Concise) => format!(
+                "{} ({: >2.0}%)",
+                format_duration_concise(self.displayed_total_time(player)),
+                100.0 * (player.total_time.as_secs_f32() / all_total_time.as_secs_f32()),
+            ),
+            (TimeDisplayMode::Shown, TextDetailMode::Detailed) => format!(
+                "{} ({: >2.0}%) -- ({} turns; avg: {}, max: {}, median: {}{})",
+                format_duration_detailed(self.displayed_total_time(player)),
+                100.0 * (player.total_time.as_secs_f32() / all_total_time.as_secs_f32()),
+                player.stats.num_turns(),
+                format_duration_stats(player.average_turn()),
+                format_duration_stats(player.stats.max_turn()),
+                format_duration_stats(player.stats.median_turn()),
+                match round_stats.early_late_average(i) {
+                    Some((early, late)) => format!(
+                        ", early/late avg: {}/{}",
+                        format_duration_stats(Some(early)),
+                        format_duration_stats(Some(late))
+                    ),
+                    None => "".to_string(),
+                }
+            ),
+        };
 
-            let text_line = if text_line_info.is_empty() {
-                text_line_name
-            } else {
-                format!("{text_line_name}: {text_line_info}")
-            };
+        if text_line_info.is_empty() {
+            text_line_name
+        } else {
+            format!("{text_line_name}: {text_line_info}")
+        }
+    }
 
+    /// `text_lines` from [Self::text_lines]: one per player, then any team totals and round
+    /// summary.
+    fn draw_player_text(
+        &self,
+        layout: &Layout,
+        text_lines: &[(String, mq::Color)],
+        players: &[Player],
+    ) {
+        let font_size = layout.text_font_size;
+        // Padding and line thickness are sized for the max font size.
+        let scale = font_size / layout::MAX_FONT_SIZE;
+        for (i, (text_line, color)) in text_lines.iter().enumerate() {
             // TODO:3 use friendlier monospace font
-            let player_text_y = PLAYER_TEXT_Y
-                + ((PLAYER_TEXT_LINE_BUFFER + PLAYER_TEXT_FONT_SIZE) * (i as f32 + 1.0));
-            mq::draw_text(
-                &text_line,
-                PLAYER_TEXT_X,
-                player_text_y,
-                PLAYER_TEXT_FONT_SIZE,
-                player.display_color,
-            );
+            let text_y = layout.text_line_y(i);
+            mq::draw_text(text_line, layout.text_x, text_y, font_size, *color);
+            let Some(player) = players.get(i) else {
+                continue;
+            };
 
-            if i == current_player_index {
-                let text_dimension =
-                    mq::measure_text(&text_line, None, PLAYER_TEXT_FONT_SIZE as u16, 1.0);
+            let text_dimension = mq::measure_text(text_line, None, font_size as u16, 1.0);
+            if self.is_flagged(player) {
+                let strike_y = text_y - text_dimension.offset_y / 2.0;
+                mq::draw_line(
+                    layout.text_x,
+                    strike_y,
+                    layout.text_x + text_dimension.width,
+                    strike_y,
+                    FLAGGED_LINE_THICKNESS * scale,
+                    FLAGGED_LINE_COLOR,
+                );
+            }
+
+            if self.is_active(i) {
                 // Magic numbers are rectangle padding, which just "looks right".
                 mq::draw_rectangle_lines(
-                    PLAYER_TEXT_X - 5.0,
-                    player_text_y - text_dimension.height - 4.0,
-                    text_dimension.width + 10.0,
-                    text_dimension.height + 18.0,
-                    PLAYER_RECTANGLE_THICKNESS,
-                    mq::WHITE,
+                    layout.text_x - 5.0 * scale,
+                    text_y - text_dimension.height - 4.0 * scale,
+                    text_dimension.width + 10.0 * scale,
+                    text_dimension.height + 18.0 * scale,
+                    PLAYER_RECTANGLE_THICKNESS * scale,
+                    match self.current_limit_status() {
+                        LimitStatus::WithinLimits => PLAYER_RECTANGLE_COLOR,
+                        LimitStatus::OverSoftLimit => PLAYER_RECTANGLE_OVER_SOFT_LIMIT_COLOR,
+                        LimitStatus::OverHardLimit { .. } => PLAYER_RECTANGLE_OVER_HARD_LIMIT_COLOR,
+                    },
                 );
             }
         }
     }
 
-    fn draw_pie(players: &[Player], current_player_index: usize, all_total_time: Duration) {
+    /// Team members' slices are next to each other, with the team's total in a ring around them.
+    /// Phases get grey slices after the players.
+    fn draw_pie(&self, layout: &Layout, players: &[Player]) {
+        let all_total_time = all_total_time(players) + self.phase_times.total();
         let circle_sides = 100;
         let radius = 0.0;
         // Offset circle so 0 degrees is north.
         let rotation_offset = -90.0;
 
+        let mut slice_start_degrees = vec![0.0; players.len()];
         let mut current_start_degree = 0.0;
-        for (i, player) in players.iter().enumerate() {
+        for i in shared_turn::team_grouped_order(players) {
+            let player = &players[i];
             // portion = [0, 1]
             let player_slice_portion =
                 player.total_time.as_secs_f32() / all_total_time.as_secs_f32();
             let player_slice_degrees = 360.0 * player_slice_portion;
-            let thickness_multiplier = if i == current_player_index {
-                PIE_THICKNESS_CURRENT_TURN_MULTIPLIER
+            let thickness_multiplier = if self.is_active(i) {
+                PIE_RADIUS_CURRENT_TURN_MULTIPLIER
             } else {
                 1.0
             };
             mq::draw_arc(
-                PIE_X,
-                PIE_Y,
+                layout.pie_center.x,
+                layout.pie_center.y,
                 circle_sides,
                 radius,
                 current_start_degree + rotation_offset,
-                PIE_THICKNESS * thickness_multiplier,
+                layout.pie_radius * thickness_multiplier,
                 player_slice_degrees,
                 player.display_color,
             );
 
+            slice_start_degrees[i] = current_start_degree;
             current_start_degree += player_slice_degrees;
         }
+
+        for (phase, time) in self.phase_times.iter() {
+            let phase_slice_degrees = 360.0 * (time.as_secs_f32() / all_total_time.as_secs_f32());
+            let thickness_multiplier = if self.opt_phase == Some(phase) {
+                PIE_RADIUS_CURRENT_TURN_MULTIPLIER
+            } else {
+                1.0
+            };
+            mq::draw_arc(
+                layout.pie_center.x,
+                layout.pie_center.y,
+                circle_sides,
+                radius,
+                current_start_degree + rotation_offset,
+                layout.pie_radius * thickness_multiplier,
+                phase_slice_degrees,
+                phase.color(),
+            );
+            current_start_degree += phase_slice_degrees;
+        }
+
+        let team_ring_radius =
+            layout.pie_radius * (PIE_RADIUS_CURRENT_TURN_MULTIPLIER + TEAM_RING_GAP);
+        for team in shared_turn::teams(players) {
+            let team_portion =
+                team.total_time(players).as_secs_f32() / all_total_time.as_secs_f32();
+            mq::draw_arc(
+                layout.pie_center.x,
+                layout.pie_center.y,
+                circle_sides,
+                team_ring_radius,
+                slice_start_degrees[team.member_indices[0]] + rotation_offset,
+                layout.pie_radius * TEAM_RING_THICKNESS,
+                360.0 * team_portion,
+                team.color,
+            );
+        }
     }
 }
 
+fn all_total_time(players: &[Player]) -> Duration {
+    players.iter().map(|player| player.total_time).sum()
+}
+
 fn format_duration_concise(duration: Duration) -> String {
     let total_seconds = duration.as_secs();
     let hours = total_seconds / 3600;
@@ -308,11 +1265,23 @@ fn format_duration_stats(duration: Option<Duration>) -> String {
     format!("{minutes:02}:{seconds:02}")
 }
 
+/// The option after `current` in `options`, wrapping around.
+fn next_option<T: Copy + PartialEq>(options: &[T], current: T) -> T {
+    let index = options.iter().position(|option| *option == current);
+    options[index.map_or(0, |i| (i + 1) % options.len())]
+}
+
 struct Player {
     display_name: String,
     display_color: mq::Color,
+    team: Option<String>,
+    /// Includes `shared_time`.
     total_time: Duration,
+    /// Time charged to the player during shared turns.
+    shared_time: Duration,
     stats: PlayerTurnDurationStats,
+    /// Ran out of banked time. It's for good, even if an increment tops the bank back up later.
+    flagged: bool,
 }
 
 impl Player {
@@ -320,8 +1289,11 @@ impl Player {
         Self {
             display_name: display_name.into(),
             display_color,
+            team: None,
             total_time: Duration::ZERO,
+            shared_time: Duration::ZERO,
             stats: PlayerTurnDurationStats::new(),
+            flagged: false,
         }
     }
 
@@ -329,6 +1301,22 @@ impl Player {
         self.total_time += elapsed_tick_time;
         self.stats.tick_frame(elapsed_tick_time);
     }
+
+    /// Average of the player's own turns, not counting shared turns.
+    pub(crate) fn average_turn(&self) -> Option<Duration> {
+        match self.stats.num_turns() {
+            0 => None,
+            num_turns => Some((self.total_time - self.shared_time) / num_turns as u32),
+        }
+    }
+}
+
+/// A turn as it was when `PlayerTurnDurationStats::end_turn()` ended it.
+#[derive(Copy, Clone)]
+pub(crate) struct EndedTurn {
+    duration: Duration,
+    // Whether it was added to the completed turns.
+    counted: bool,
 }
 
 struct PlayerTurnDurationStats {
@@ -347,12 +1335,36 @@ impl PlayerTurnDurationStats {
         }
     }
 
-    pub(crate) fn end_turn(&mut self) {
-        if self.current_turn_duration >= Self::DONT_COUNT_TURN_THRESHOLD {
-            self.completed_turn_durations
-                .push(self.current_turn_duration);
+    pub(crate) fn end_turn(&mut self) -> EndedTurn {
+        let ended_turn = EndedTurn {
+            duration: self.current_turn_duration,
+            counted: self.current_turn_duration >= Self::DONT_COUNT_TURN_THRESHOLD,
+        };
+        if ended_turn.counted {
+            self.completed_turn_durations.push(ended_turn.duration);
         }
         self.current_turn_duration = Duration::ZERO;
+        ended_turn
+    }
+
+    /// Reset the current turn, returning how long it was.
+    pub(crate) fn take_current_turn(&mut self) -> Duration {
+        std::mem::take(&mut self.current_turn_duration)
+    }
+
+    /// Undo `end_turn()`, continuing that turn with `additional_time` added to it.
+    pub(crate) fn resume_ended_turn(&mut self, ended_turn: EndedTurn, additional_time: Duration) {
+        if ended_turn.counted {
+            let mut completed_turns = std::mem::take(&mut self.completed_turn_durations).into_vec();
+            if let Some(i) = completed_turns
+                .iter()
+                .position(|d| *d == ended_turn.duration)
+            {
+                completed_turns.swap_remove(i);
+            }
+            self.completed_turn_durations = BinaryHeap::from(completed_turns);
+        }
+        self.current_turn_duration = ended_turn.duration + additional_time;
     }
 
     pub(crate) fn tick_frame(&mut self, elapsed_tick_time: Duration) {
@@ -397,7 +1409,7 @@ impl PlayerTurnDurationStats {
             return None;
         }
 
-        if 0 == sorted_turns_vec.len() % 2 {
+        if sorted_turns_vec.len().is_multiple_of(2) {
             // even length
             let median_index_1 = sorted_turns_vec.len() / 2 - 1;
             let median_index_2 = sorted_turns_vec.len() / 2;
@@ -412,8 +1424,146 @@ impl PlayerTurnDurationStats {
 
 #[cfg(test)]
 mod tests {
+    use super::{next_option, SharedTimePolicy, TimeLimits, TurnDirection, TurnTimeTracker};
+    use crate::phases::{GamePhase, PHASE_OPTIONS};
+    use crate::session::SessionSnapshot;
+    use crate::time_limits::{ClockMode, TURN_LIMIT_OPTIONS};
+    use better_quad::bq::Timestamp;
+    use macroquad::prelude as mq;
     use std::time::Duration;
 
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn test_undo_player_change() {
+        let mut tracker = TurnTimeTracker::with_players(vec![("A", mq::RED), ("B", mq::BLUE)]);
+
+        tracker.tick_current_player(secs(10));
+        tracker.change_player(1, TurnDirection::Forward);
+        tracker.tick_current_player(secs(20));
+        tracker.change_player(0, TurnDirection::Forward); // accidental
+        tracker.tick_current_player(secs(3));
+        tracker.undo_player_change();
+
+        let (players, current_index) = tracker.players.raw();
+        assert_eq!(1, current_index);
+        assert_eq!(secs(10), players[0].total_time);
+        assert_eq!(1, players[0].stats.num_turns());
+        assert_eq!(secs(23), players[1].total_time);
+        assert_eq!(secs(23), players[1].stats.current_turn_duration);
+        assert!(players[1].stats.completed_turn_durations.is_empty());
+
+        // Undo back to the start, then nothing left to undo.
+        tracker.undo_player_change();
+        tracker.undo_player_change();
+        let (players, current_index) = tracker.players.raw();
+        assert_eq!(0, current_index);
+        assert_eq!(secs(33), players[0].total_time);
+        assert_eq!(secs(33), players[0].stats.current_turn_duration);
+        assert_eq!(Duration::ZERO, players[1].total_time);
+        assert_eq!(0, players[1].stats.num_turns());
+    }
+
+    #[test]
+    fn test_rounds_count_wraps() {
+        let mut tracker =
+            TurnTimeTracker::with_players(vec![("A", mq::RED), ("B", mq::BLUE), ("C", mq::GREEN)]);
+        assert_eq!(1, tracker.turn_log.current_round());
+
+        tracker.change_player(1, TurnDirection::Forward);
+        tracker.change_player(2, TurnDirection::Forward);
+        tracker.change_player(0, TurnDirection::Forward);
+        assert_eq!(2, tracker.turn_log.current_round());
+
+        // Stepping back past the first player returns to the previous round.
+        tracker.change_player(2, TurnDirection::Backward);
+        assert_eq!(1, tracker.turn_log.current_round());
+        // Round 2's turn is still in the stats.
+        assert_eq!(2, tracker.round_stats().round_durations().len());
+        tracker.change_player(1, TurnDirection::Backward);
+        assert_eq!(1, tracker.turn_log.current_round());
+
+        // Jumping ahead to an earlier seat wraps around.
+        tracker.change_player(0, TurnDirection::Forward);
+        assert_eq!(2, tracker.turn_log.current_round());
+        tracker.undo_player_change();
+        assert_eq!(1, tracker.turn_log.current_round());
+    }
+
+    #[test]
+    fn test_next_player_skips_flagged() {
+        let mut tracker =
+            TurnTimeTracker::with_players(vec![("A", mq::RED), ("B", mq::BLUE), ("C", mq::GREEN)])
+                .with_time_limits(TimeLimits {
+                    time_bank: Some(secs(60)),
+                    skip_flagged_players: true,
+                    ..Default::default()
+                });
+
+        tracker.change_player(1, TurnDirection::Forward);
+        tracker.tick_current_player(secs(61)); // B flags
+        assert!(tracker.is_flagged(tracker.players.current()));
+        tracker.change_player(2, TurnDirection::Forward);
+        assert_eq!(0, tracker.next_player_index());
+        tracker.change_player(0, TurnDirection::Forward);
+        assert_eq!(2, tracker.next_player_index());
+
+        tracker.time_limits.skip_flagged_players = false;
+        assert_eq!(1, tracker.next_player_index());
+    }
+
+    #[test]
+    fn test_flag_survives_increment() {
+        let mut tracker = TurnTimeTracker::with_players(vec![("A", mq::RED), ("B", mq::BLUE)])
+            .with_time_limits(TimeLimits {
+                time_bank: Some(secs(60)),
+                clock_mode: ClockMode::Increment(secs(10)),
+                skip_flagged_players: true,
+                ..Default::default()
+            });
+
+        tracker.tick_current_player(secs(65)); // A flags
+        tracker.change_player(1, TurnDirection::Forward);
+        // The increment puts A's bank back above zero, but A is still out.
+        let player_a = &tracker.players.raw().0[0];
+        assert!(tracker.is_flagged(player_a));
+        assert_eq!(Duration::ZERO, tracker.displayed_total_time(player_a));
+        assert_eq!(1, tracker.next_player_index());
+
+        let restored = SessionSnapshot::parse(&SessionSnapshot::capture(&tracker).to_json())
+            .unwrap()
+            .into_tracker(Timestamp::now());
+        assert!(restored.is_flagged(&restored.players.raw().0[0]));
+    }
+
+    #[test]
+    fn test_shared_turn() {
+        let mut tracker =
+            TurnTimeTracker::with_players(vec![("A", mq::RED), ("B", mq::BLUE), ("C", mq::GREEN)]);
+        tracker.tick_current_player(secs(10));
+
+        // A's turn is on hold while A and C share a turn.
+        tracker.toggle_shared_turn();
+        tracker.jump_to_player(2);
+        tracker.tick_current_player(secs(6));
+        tracker.shared_time_policy = SharedTimePolicy::Duplicate;
+        tracker.tick_current_player(secs(1));
+        tracker.toggle_shared_turn();
+        tracker.tick_current_player(secs(2));
+
+        let (players, current_index) = tracker.players.raw();
+        assert_eq!(0, current_index);
+        assert_eq!(secs(16), players[0].total_time);
+        assert_eq!(secs(4), players[0].shared_time);
+        assert_eq!(secs(12), players[0].stats.current_turn_duration);
+        assert_eq!(Some(secs(12)), players[0].average_turn());
+        assert_eq!(Duration::ZERO, players[1].total_time);
+        assert_eq!(secs(4), players[2].total_time);
+        assert_eq!(0, players[2].stats.num_turns());
+    }
+
     #[test]
     fn test_format_duration_detailed() {
         let test_cases = [
@@ -428,4 +1578,14 @@ mod tests {
             assert_eq!(expected_output, &actual_output);
         }
     }
+
+    #[test]
+    fn test_next_option_wraps() {
+        assert_eq!(Some(secs(30)), next_option(&TURN_LIMIT_OPTIONS, None));
+        assert_eq!(None, next_option(&TURN_LIMIT_OPTIONS, Some(secs(10 * 60))));
+        // Values not in the options (e.g. hand edited saves) restart the cycle.
+        assert_eq!(None, next_option(&TURN_LIMIT_OPTIONS, Some(secs(7))));
+        assert_eq!(PHASE_OPTIONS[1], next_option(&PHASE_OPTIONS, None));
+        assert_eq!(None, next_option(&PHASE_OPTIONS, Some(GamePhase::Scoring)));
+    }
 }
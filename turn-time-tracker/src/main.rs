@@ -1,9 +1,9 @@
 use better_quad::StatefulGui;
 use macroquad::prelude as mq;
-use turn_time_tracker::TurnTimeTracker;
+use turn_time_tracker::TurnTimeTrackerApp;
 
 fn window_conf() -> mq::Conf {
-    TurnTimeTracker::main_conf()
+    TurnTimeTrackerApp::main_conf()
 }
 
 #[macroquad::main(window_conf)]
@@ -12,10 +12,8 @@ async fn main() {
     better_quad::run_gui(example_turn_time_tracker()).await
 }
 
-fn example_turn_time_tracker() -> TurnTimeTracker {
-    // TODO:2 replace with dynamic player/color selection
-    // https://github.com/not-fl3/particles-editor/blob/master/src/main.rs#L13-L130
-    // https://docs.rs/macroquad/latest/src/events/events.rs.html
+fn example_turn_time_tracker() -> TurnTimeTrackerApp {
+    // Shown on the setup screen until a roster has been saved.
     let players = vec![
         ("Marceline", mq::YELLOW),
         ("Bonnibel", mq::SKYBLUE),
@@ -27,5 +25,5 @@ fn example_turn_time_tracker() -> TurnTimeTracker {
         ("Cranberry", mq::RED),
     ];
 
-    TurnTimeTracker::with_players(players)
+    TurnTimeTrackerApp::from_launch_args(players)
 }
@@ -0,0 +1,113 @@
+//! Non-player game phases, like setting up or a break, timed separately from the players.
+use macroquad::prelude as mq;
+use std::time::Duration;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum GamePhase {
+    Setup,
+    Rules,
+    Break,
+    Scoring,
+}
+
+pub(crate) const GAME_PHASES: [GamePhase; 4] = [
+    GamePhase::Setup,
+    GamePhase::Rules,
+    GamePhase::Break,
+    GamePhase::Scoring,
+];
+
+/// Cycled through by the phase key, `None` being back to the players' turns.
+pub(crate) const PHASE_OPTIONS: [Option<GamePhase>; 5] = [
+    None,
+    Some(GamePhase::Setup),
+    Some(GamePhase::Rules),
+    Some(GamePhase::Break),
+    Some(GamePhase::Scoring),
+];
+
+impl GamePhase {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Setup => "Setup",
+            Self::Rules => "Rules",
+            Self::Break => "Break",
+            Self::Scoring => "Scoring",
+        }
+    }
+
+    pub(crate) fn parse(label: &str) -> Result<Self, String> {
+        GAME_PHASES
+            .into_iter()
+            .find(|phase| phase.label() == label)
+            .ok_or_else(|| format!("unknown game phase '{label}'"))
+    }
+
+    /// Shades of grey, dark enough to stand out from the background.
+    pub(crate) fn color(self) -> mq::Color {
+        let shade = match self {
+            Self::Setup => 0.25,
+            Self::Rules => 0.35,
+            Self::Break => 0.45,
+            Self::Scoring => 0.55,
+        };
+        mq::Color::new(shade, shade, shade, 1.0)
+    }
+}
+
+/// Total time spent in each phase.
+#[derive(Default)]
+pub(crate) struct PhaseTimes {
+    /// Parallel to [GAME_PHASES].
+    totals: [Duration; GAME_PHASES.len()],
+}
+
+impl PhaseTimes {
+    pub(crate) fn from_totals(totals: [Duration; GAME_PHASES.len()]) -> Self {
+        Self { totals }
+    }
+
+    pub(crate) fn tick(&mut self, phase: GamePhase, elapsed_tick_time: Duration) {
+        self.totals[phase as usize] += elapsed_tick_time;
+    }
+
+    pub(crate) fn total(&self) -> Duration {
+        self.totals.iter().sum()
+    }
+
+    /// Every phase with its time, in [GAME_PHASES] order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (GamePhase, Duration)> + '_ {
+        GAME_PHASES.into_iter().zip(self.totals.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GamePhase, PhaseTimes, GAME_PHASES};
+    use std::time::Duration;
+
+    #[test]
+    fn test_phase_times() {
+        let mut phase_times = PhaseTimes::default();
+        phase_times.tick(GamePhase::Rules, Duration::from_secs(5));
+        phase_times.tick(GamePhase::Break, Duration::from_secs(10));
+        phase_times.tick(GamePhase::Rules, Duration::from_secs(1));
+
+        let secs = Duration::from_secs;
+        assert_eq!(
+            vec![
+                (GamePhase::Setup, secs(0)),
+                (GamePhase::Rules, secs(6)),
+                (GamePhase::Break, secs(10)),
+                (GamePhase::Scoring, secs(0)),
+            ],
+            phase_times.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(secs(16), phase_times.total());
+
+        for phase in GAME_PHASES {
+            assert_eq!(Ok(phase), GamePhase::parse(phase.label()));
+        }
+        assert!(GamePhase::parse("Lunch").is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! End of session reports: every turn in order, a summary per player, and the time in each game
+//! phase, as CSV or JSON. The JSON also has the total time of each round and team.
+//!
+//! Offsets and durations are seconds of tracked time, so pauses aren't included. Neither are shared
+//! turns, which only show up in the player and team totals, or phases.
+use crate::rounds::RoundStats;
+use crate::shared_turn;
+use crate::TurnTimeTracker;
+use nanoserde::SerJson;
+use std::time::Duration;
+
+pub(crate) const TURNS_CSV_FILE: &str = "turn-time-tracker-turns.csv";
+pub(crate) const PLAYERS_CSV_FILE: &str = "turn-time-tracker-players.csv";
+pub(crate) const PHASES_CSV_FILE: &str = "turn-time-tracker-phases.csv";
+pub(crate) const JSON_FILE: &str = "turn-time-tracker-report.json";
+
+// `pub` since nanoserde derives don't support `pub(crate)`. The module is private anyway.
+#[derive(SerJson)]
+pub struct SessionReport {
+    turns: Vec<TurnRow>,
+    players: Vec<PlayerRow>,
+    rounds: Vec<RoundRow>,
+    teams: Vec<TeamRow>,
+    phases: Vec<PhaseRow>,
+}
+
+#[derive(SerJson)]
+struct TurnRow {
+    /// 1-based, across all players.
+    turn: usize,
+    round: usize,
+    player: String,
+    start_offset_s: f64,
+    duration_s: f64,
+    /// Quick presses to skip a player aren't counted in the player stats.
+    counted: bool,
+}
+
+#[derive(SerJson)]
+struct PlayerRow {
+    player: String,
+    /// Empty for no team.
+    team: String,
+    turns: usize,
+    total_s: f64,
+    /// Part of `total_s` from shared turns.
+    shared_s: f64,
+    average_s: Option<f64>,
+    max_s: Option<f64>,
+    median_s: Option<f64>,
+}
+
+#[derive(SerJson)]
+struct RoundRow {
+    round: usize,
+    /// The last round is unfinished.
+    duration_s: f64,
+}
+
+#[derive(SerJson)]
+struct TeamRow {
+    team: String,
+    players: Vec<String>,
+    total_s: f64,
+}
+
+#[derive(SerJson)]
+struct PhaseRow {
+    phase: String,
+    total_s: f64,
+}
+
+impl SessionReport {
+    pub(crate) fn new(tracker: &TurnTimeTracker) -> Self {
+        let players = tracker.players.raw().0;
+        let turns = tracker.turns();
+
+        let mut start_offset = Duration::ZERO;
+        let turn_rows = turns
+            .iter()
+            .enumerate()
+            .map(|(i, turn)| {
+                let row = TurnRow {
+                    turn: i + 1,
+                    round: turn.round,
+                    player: players[turn.player_index].display_name.clone(),
+                    start_offset_s: to_secs(start_offset),
+                    duration_s: to_secs(turn.duration),
+                    counted: turn.counted,
+                };
+                start_offset += turn.duration;
+                row
+            })
+            .collect();
+
+        let player_rows = players
+            .iter()
+            .map(|player| PlayerRow {
+                player: player.display_name.clone(),
+                team: player.team.clone().unwrap_or_default(),
+                turns: player.stats.num_turns(),
+                total_s: to_secs(player.total_time),
+                shared_s: to_secs(player.shared_time),
+                average_s: player.average_turn().map(to_secs),
+                max_s: player.stats.max_turn().map(to_secs),
+                median_s: player.stats.median_turn().map(to_secs),
+            })
+            .collect();
+
+        let round_stats = RoundStats::new(&turns, players.len(), tracker.turn_log.current_round());
+        let round_rows = round_stats
+            .round_durations()
+            .iter()
+            .enumerate()
+            .map(|(i, duration)| RoundRow {
+                round: i + 1,
+                duration_s: to_secs(*duration),
+            })
+            .collect();
+
+        let team_rows = shared_turn::teams(players)
+            .into_iter()
+            .map(|team| TeamRow {
+                players: team
+                    .member_indices
+                    .iter()
+                    .map(|i| players[*i].display_name.clone())
+                    .collect(),
+                total_s: to_secs(team.total_time(players)),
+                team: team.name,
+            })
+            .collect();
+
+        let phase_rows = tracker
+            .phase_times
+            .iter()
+            .map(|(phase, time)| PhaseRow {
+                phase: phase.label().to_string(),
+                total_s: to_secs(time),
+            })
+            .collect();
+
+        Self {
+            turns: turn_rows,
+            players: player_rows,
+            rounds: round_rows,
+            teams: team_rows,
+            phases: phase_rows,
+        }
+    }
+
+    pub(crate) fn turns_csv(&self) -> String {
+        let mut csv = "turn,round,player,start_offset_s,duration_s,counted\n".to_string();
+        for row in &self.turns {
+            csv += &format!(
+                "{},{},{},{:.3},{:.3},{}\n",
+                row.turn,
+                row.round,
+                csv_field(&row.player),
+                row.start_offset_s,
+                row.duration_s,
+                row.counted
+            );
+        }
+        csv
+    }
+
+    pub(crate) fn players_csv(&self) -> String {
+        let optional =
+            |opt_secs: Option<f64>| opt_secs.map_or(String::new(), |s| format!("{s:.3}"));
+        let mut csv = "player,team,turns,total_s,shared_s,average_s,max_s,median_s\n".to_string();
+        for row in &self.players {
+            csv += &format!(
+                "{},{},{},{:.3},{:.3},{},{},{}\n",
+                csv_field(&row.player),
+                csv_field(&row.team),
+                row.turns,
+                row.total_s,
+                row.shared_s,
+                optional(row.average_s),
+                optional(row.max_s),
+                optional(row.median_s)
+            );
+        }
+        csv
+    }
+
+    pub(crate) fn phases_csv(&self) -> String {
+        let mut csv = "phase,total_s\n".to_string();
+        for row in &self.phases {
+            csv += &format!("{},{:.3}\n", row.phase, row.total_s);
+        }
+        csv
+    }
+
+    pub(crate) fn to_json(&self) -> String {
+        self.serialize_json()
+    }
+}
+
+/// Whole milliseconds, to keep the output free of float noise.
+fn to_secs(duration: Duration) -> f64 {
+    duration.as_millis() as f64 / 1000.0
+}
+
+/// Quote fields containing CSV syntax, doubling any quotes.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SessionReport;
+    use crate::phases::GamePhase;
+    use crate::{TurnDirection, TurnTimeTracker};
+    use macroquad::prelude as mq;
+    use std::time::Duration;
+
+    #[test]
+    fn test_report_csv() {
+        let mut tracker =
+            TurnTimeTracker::with_players(vec![("Leo", mq::RED), ("Dr \"Who\", PhD", mq::BLUE)]);
+        tracker.tick_current_player(Duration::from_secs(10));
+        tracker.change_player(1, TurnDirection::Forward);
+        tracker.tick_current_player(Duration::from_millis(300)); // quick skip
+        tracker.change_player(0, TurnDirection::Forward);
+        tracker.tick_current_player(Duration::from_millis(2_500));
+        tracker.opt_phase = Some(GamePhase::Scoring);
+        tracker.tick_current_player(Duration::from_secs(60));
+
+        let report = SessionReport::new(&tracker);
+        assert_eq!(
+            "turn,round,player,start_offset_s,duration_s,counted\n\
+            1,1,Leo,0.000,10.000,true\n\
+            2,1,\"Dr \"\"Who\"\", PhD\",10.000,0.300,false\n\
+            3,2,Leo,10.300,2.500,true\n",
+            report.turns_csv()
+        );
+        assert_eq!(
+            "player,team,turns,total_s,shared_s,average_s,max_s,median_s\n\
+            Leo,,2,12.500,0.000,6.250,10.000,6.250\n\
+            \"Dr \"\"Who\"\", PhD\",,0,0.300,0.000,,,\n",
+            report.players_csv()
+        );
+        assert_eq!(
+            "phase,total_s\n\
+            Setup,0.000\n\
+            Rules,0.000\n\
+            Break,0.000\n\
+            Scoring,60.000\n",
+            report.phases_csv()
+        );
+        let json = report.to_json();
+        assert!(json.contains("\"median_s\":6.25"));
+        assert!(json.contains("\"rounds\":[{\"round\":1,\"duration_s\":10.3},"));
+    }
+}
@@ -0,0 +1,292 @@
+//! Player names and colors in turn order, and the text format to save them in.
+//!
+//! One player per line as `Name:#RRGGBB`, or `Name:#RRGGBB@Team` for players on a team. Rosters
+//! can also be written by hand as TOML or JSON files, see [parse_file].
+use better_quad::bq;
+use macroquad::prelude as mq;
+use nanoserde::{DeJson, Toml, TomlParser};
+
+const SAVED_ROSTER_KEY: &str = "turn-time-tracker-roster";
+
+pub(crate) const MAX_NAME_LEN: usize = 16;
+
+#[derive(Clone)]
+pub(crate) struct RosterPlayer {
+    pub(crate) name: String,
+    pub(crate) color: mq::Color,
+    pub(crate) team: Option<String>,
+}
+
+impl RosterPlayer {
+    pub(crate) fn new(name: impl Into<String>, color: mq::Color) -> Self {
+        Self {
+            name: name.into(),
+            color,
+            team: None,
+        }
+    }
+
+    pub(crate) fn with_team(mut self, team: Option<String>) -> Self {
+        self.team = team;
+        self
+    }
+
+    /// Parse `Name:#RRGGBB` or `Name:#RRGGBB@Team`. The name may itself contain `:`.
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let Some((name, color_and_team)) = text.rsplit_once(':') else {
+            return Err(format!("'{text}' should look like 'Name:#RRGGBB'"));
+        };
+        let (hex_color, opt_team) = match color_and_team.split_once('@') {
+            Some((hex_color, team)) => (hex_color, Some(team.trim().to_string())),
+            None => (color_and_team, None),
+        };
+        let Some(color) = bq::color_from_hex_str(hex_color.trim()) else {
+            return Err(format!(
+                "'{hex_color}' isn't a hex color like #FF8800 (player '{name}')"
+            ));
+        };
+        Ok(Self::new(name.trim(), color).with_team(opt_team))
+    }
+
+    pub(crate) fn format(&self) -> String {
+        let name_and_color = format!("{}:{}", self.name, bq::color_to_hex_str(self.color));
+        match &self.team {
+            Some(team) => format!("{name_and_color}@{team}"),
+            None => name_and_color,
+        }
+    }
+}
+
+/// Check the roster can be used to start tracking.
+pub(crate) fn validate(players: &[RosterPlayer]) -> Result<(), String> {
+    if players.is_empty() {
+        return Err("Add at least one player".to_string());
+    }
+
+    for (i, player) in players.iter().enumerate() {
+        if player.name.trim().is_empty() {
+            return Err(format!("Player {} needs a name", i + 1));
+        }
+        if player.name.chars().count() > MAX_NAME_LEN {
+            return Err(format!(
+                "'{}' is longer than {MAX_NAME_LEN} characters",
+                player.name
+            ));
+        }
+        if players[..i]
+            .iter()
+            .any(|other| other.name.eq_ignore_ascii_case(&player.name))
+        {
+            return Err(format!("'{}' is listed more than once", player.name));
+        }
+        if let Some(team) = &player.team {
+            if team.is_empty() || team.contains(':') || team.chars().count() > MAX_NAME_LEN {
+                return Err(format!(
+                    "'{}' has an invalid team name (1 to {MAX_NAME_LEN} characters, no ':')",
+                    player.name
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn parse_lines(text: &str) -> Result<Vec<RosterPlayer>, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(RosterPlayer::parse)
+        .collect()
+}
+
+pub(crate) fn format_lines(players: &[RosterPlayer]) -> String {
+    players
+        .iter()
+        .map(|p| p.format() + "\n")
+        .collect::<String>()
+}
+
+/// Parse a hand written roster file. JSON if the file name ends in `.json`, otherwise TOML.
+///
+/// ```toml
+/// [[players]]
+/// name = "Marceline"
+/// color = "#FDF900"
+/// team = "Vampires" # optional
+/// ```
+///
+/// ```json
+/// { "players": [{ "name": "Marceline", "color": "#FDF900", "team": "Vampires" }] }
+/// ```
+pub(crate) fn parse_file(file_name: &str, contents: &str) -> Result<Vec<RosterPlayer>, String> {
+    let file_players = if file_name.to_lowercase().ends_with(".json") {
+        parse_json_file(contents)
+    } else {
+        parse_toml_file(contents)
+    }
+    .map_err(|e| format!("{file_name}: {e}"))?;
+
+    file_players
+        .into_iter()
+        .map(|(name, color, opt_team)| {
+            RosterPlayer::parse(&format!("{name}:{color}")).map(|p| p.with_team(opt_team))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("{file_name}: {e}"))
+}
+
+#[derive(DeJson)]
+struct JsonRosterFile {
+    players: Vec<JsonRosterPlayer>,
+}
+
+#[derive(DeJson)]
+struct JsonRosterPlayer {
+    name: String,
+    color: String,
+    // Empty for no team. Not an `Option`, since the derive trips clippy on those.
+    #[nserde(default)]
+    team: String,
+}
+
+/// Name, color, and team of each player in a roster file.
+type FilePlayer = (String, String, Option<String>);
+
+fn parse_json_file(contents: &str) -> Result<Vec<FilePlayer>, String> {
+    let roster_file = JsonRosterFile::deserialize_json(contents)
+        .map_err(|e| format!("invalid JSON roster ({e})"))?;
+    Ok(roster_file
+        .players
+        .into_iter()
+        .map(|p| {
+            let opt_team = (!p.team.is_empty()).then_some(p.team);
+            (p.name, p.color, opt_team)
+        })
+        .collect())
+}
+
+fn parse_toml_file(contents: &str) -> Result<Vec<FilePlayer>, String> {
+    let toml = TomlParser::parse(contents).map_err(|e| {
+        format!(
+            "invalid TOML roster (line {}, col {}: {})",
+            e.line, e.col, e.msg
+        )
+    })?;
+    let Some(Toml::Array(players)) = toml.get("players") else {
+        return Err("expected [[players]] tables".to_string());
+    };
+
+    players
+        .iter()
+        .enumerate()
+        .map(
+            |(i, player)| match (player.get("name"), player.get("color"), player.get("team")) {
+                (Some(Toml::Str(name)), Some(Toml::Str(color)), None) => {
+                    Ok((name.clone(), color.clone(), None))
+                }
+                (Some(Toml::Str(name)), Some(Toml::Str(color)), Some(Toml::Str(team))) => {
+                    Ok((name.clone(), color.clone(), Some(team.clone())))
+                }
+                _ => Err(format!(
+                    "player {} needs string 'name' and 'color' keys, and optionally 'team'",
+                    i + 1
+                )),
+            },
+        )
+        .collect()
+}
+
+/// Roster from the last time tracking was started, if any.
+pub(crate) fn load_saved() -> Option<Vec<RosterPlayer>> {
+    let players = parse_lines(&bq::storage_load(SAVED_ROSTER_KEY)?).ok()?;
+    validate(&players).ok()?;
+    Some(players)
+}
+
+pub(crate) fn save(players: &[RosterPlayer]) {
+    if let Err(e) = bq::storage_store(SAVED_ROSTER_KEY, &format_lines(players)) {
+        mq::warn!("Failed to save roster: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_file, parse_lines, validate, RosterPlayer};
+
+    #[test]
+    fn test_parse_player() {
+        let player = RosterPlayer::parse("Dr. Who:#0000ff").unwrap();
+        assert_eq!("Dr. Who", player.name);
+        assert_eq!("Dr. Who:#0000FF", player.format());
+
+        let player = RosterPlayer::parse("Team: Red:FF0000").unwrap();
+        assert_eq!("Team: Red", player.name);
+
+        let player = RosterPlayer::parse("Leo:#00FF00@Cats").unwrap();
+        assert_eq!(Some("Cats".to_string()), player.team);
+        assert_eq!("Leo:#00FF00@Cats", player.format());
+
+        assert!(RosterPlayer::parse("Marceline").is_err());
+        assert!(RosterPlayer::parse("Marceline:yellow").is_err());
+        assert!(RosterPlayer::parse("Marceline:#FFFF0").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let players = parse_lines("A:#000000\n\nB:#FFFFFF\n").unwrap();
+        assert!(validate(&players).is_ok());
+
+        let test_cases = [
+            // (roster, expected error)
+            ("", "Add at least one player"),
+            ("A:#000000\n :#FFFFFF", "Player 2 needs a name"),
+            ("Leo:#000000\nleo:#FFFFFF", "'leo' is listed more than once"),
+            (
+                "Leo:#000000@",
+                "'Leo' has an invalid team name (1 to 16 characters, no ':')",
+            ),
+        ];
+        for (roster, expected_error) in test_cases {
+            let players = parse_lines(roster).unwrap();
+            assert_eq!(Err(expected_error.to_string()), validate(&players));
+        }
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let toml = r##"
+            [[players]]
+            name = "Leo"
+            color = "#00FF00"
+
+            [[players]]
+            name = "Tiger"
+            color = "#0000FF"
+            team = "Cats"
+        "##;
+        let json = r##"{"players": [
+            {"name": "Leo", "color": "#00FF00"},
+            {"name": "Tiger", "color": "#0000FF", "team": "Cats"}
+        ]}"##;
+
+        for (file_name, contents) in [("roster.toml", toml), ("roster.JSON", json)] {
+            let players = parse_file(file_name, contents).unwrap();
+            let names = players.iter().map(|p| p.name.as_str()).collect::<Vec<_>>();
+            assert_eq!(vec!["Leo", "Tiger"], names, "{file_name}");
+            let teams = players
+                .iter()
+                .map(|p| p.team.as_deref())
+                .collect::<Vec<_>>();
+            assert_eq!(vec![None, Some("Cats")], teams, "{file_name}");
+        }
+
+        let bad_color = "[[players]]\nname = \"Leo\"\ncolor = \"green\"\n";
+        assert_eq!(
+            Err("roster.toml: 'green' isn't a hex color like #FF8800 (player 'Leo')".to_string()),
+            parse_file("roster.toml", bad_color).map(|_| ())
+        );
+        assert!(parse_file("roster.json", "{\"players\": [{\"name\": \"Leo\"}]}").is_err());
+        assert!(parse_file("roster.toml", "name = \"Leo\"").is_err());
+    }
+}
@@ -0,0 +1,121 @@
+//! Per-round stats. A round starts each time the turn order wraps back past the last player.
+use crate::turn_log::LoggedTurn;
+use std::time::Duration;
+
+pub(crate) struct RoundStats {
+    /// Total time of each round, the last being the current (unfinished) round, or the latest one
+    /// stepped back from.
+    round_durations: Vec<Duration>,
+    /// Counted turn durations of each player, by round: `[player_index][round - 1]`.
+    player_round_turns: Vec<Vec<Vec<Duration>>>,
+}
+
+impl RoundStats {
+    /// Stepping back past the first player returns to the previous round, so `turns` can be from
+    /// rounds after `current_round`. They're all counted.
+    pub(crate) fn new(turns: &[LoggedTurn], num_players: usize, current_round: usize) -> Self {
+        let num_rounds = turns
+            .iter()
+            .map(|turn| turn.round)
+            .max()
+            .unwrap_or(0)
+            .max(current_round);
+        let mut round_durations = vec![Duration::ZERO; num_rounds];
+        let mut player_round_turns = vec![vec![Vec::new(); num_rounds]; num_players];
+        for turn in turns {
+            round_durations[turn.round - 1] += turn.duration;
+            if turn.counted {
+                player_round_turns[turn.player_index][turn.round - 1].push(turn.duration);
+            }
+        }
+        Self {
+            round_durations,
+            player_round_turns,
+        }
+    }
+
+    fn completed_round_durations(&self) -> &[Duration] {
+        &self.round_durations[..self.round_durations.len() - 1]
+    }
+
+    /// 1-based round number, and the round's total time.
+    pub(crate) fn last_completed_round(&self) -> Option<(usize, Duration)> {
+        let completed = self.completed_round_durations();
+        completed
+            .last()
+            .map(|duration| (completed.len(), *duration))
+    }
+
+    /// 1-based round number, and the round's total time.
+    pub(crate) fn slowest_completed_round(&self) -> Option<(usize, Duration)> {
+        self.completed_round_durations()
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, duration)| **duration)
+            .map(|(i, duration)| (i + 1, *duration))
+    }
+
+    /// Total time of every round so far, including the current one.
+    pub(crate) fn round_durations(&self) -> &[Duration] {
+        &self.round_durations
+    }
+
+    /// A player's average turn in the first half of the rounds vs the second half, to see if they
+    /// speed up or slow down as the game goes on. `None` until there's a turn in each half.
+    pub(crate) fn early_late_average(&self, player_index: usize) -> Option<(Duration, Duration)> {
+        let rounds = &self.player_round_turns[player_index];
+        let (early_rounds, late_rounds) = rounds.split_at(rounds.len() / 2);
+        Some((average(early_rounds)?, average(late_rounds)?))
+    }
+}
+
+fn average(rounds: &[Vec<Duration>]) -> Option<Duration> {
+    let turns = rounds.iter().flatten().collect::<Vec<_>>();
+    if turns.is_empty() {
+        return None;
+    }
+    Some(turns.iter().copied().sum::<Duration>() / turns.len() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoundStats;
+    use crate::turn_log::LoggedTurn;
+    use std::time::Duration;
+
+    fn turn(player_index: usize, round: usize, seconds: u64) -> LoggedTurn {
+        LoggedTurn {
+            player_index,
+            round,
+            duration: Duration::from_secs(seconds),
+            counted: true,
+        }
+    }
+
+    #[test]
+    fn test_round_stats() {
+        let turns = [
+            turn(0, 1, 10),
+            turn(1, 1, 20),
+            turn(0, 2, 30),
+            turn(1, 2, 40),
+            turn(0, 3, 5),
+        ];
+        let stats = RoundStats::new(&turns, 2, 3);
+
+        let secs = Duration::from_secs;
+        assert_eq!(&[secs(30), secs(70), secs(5)], stats.round_durations());
+        assert_eq!(Some((2, secs(70))), stats.last_completed_round());
+        assert_eq!(Some((2, secs(70))), stats.slowest_completed_round());
+        // Round 1 vs rounds 2-3
+        assert_eq!(
+            Some((secs(10), secs(17) + Duration::from_millis(500))),
+            stats.early_late_average(0)
+        );
+        assert_eq!(Some((secs(20), secs(40))), stats.early_late_average(1));
+
+        let first_round = RoundStats::new(&turns[..2], 2, 1);
+        assert_eq!(None, first_round.slowest_completed_round());
+        assert_eq!(None, first_round.early_late_average(0));
+    }
+}
@@ -0,0 +1,406 @@
+//! Autosave of the whole tracker state, so a session survives closing (or crashing) the app and can
+//! be resumed from the setup screen.
+use crate::phases::{GamePhase, PhaseTimes, GAME_PHASES};
+use crate::roster::{self, RosterPlayer};
+use crate::shared_turn::{SharedTimePolicy, SharedTurn};
+use crate::time_limits::{TimeLimits, TimeLimitsJson};
+use crate::turn_log::{TurnChange, TurnLog};
+use crate::{
+    format_duration_concise, EndedTurn, Player, PlayerTurnDurationStats, TimerState,
+    TurnTimeTracker,
+};
+use better_quad::bq::{self, Timestamp};
+use better_quad::utils::infinite_iterator::InfiniteIterator;
+use macroquad::prelude as mq;
+use nanoserde::{DeJson, SerJson};
+use std::collections::BinaryHeap;
+use std::time::Duration;
+
+const SAVED_SESSION_KEY: &str = "turn-time-tracker-session";
+
+/// How often a running session is saved. Turn changes and pausing are saved immediately.
+pub(crate) const AUTOSAVE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Durations are whole milliseconds, so turns round-trip exactly between saving and loading.
+// `pub` since nanoserde derives don't support `pub(crate)`. The module is private anyway.
+#[derive(SerJson, DeJson)]
+pub struct SessionSnapshot {
+    /// Empty if not given, or saved before there were game names.
+    #[nserde(default)]
+    game_name: String,
+    players: Vec<PlayerSnapshot>,
+    current_index: usize,
+    turn_log: Vec<TurnChangeSnapshot>,
+    paused: bool,
+    /// No limits if saved before there were time limits.
+    #[nserde(default)]
+    time_limits: TimeLimitsJson,
+    // Defaults let sessions saved before shared turns still load.
+    #[nserde(default)]
+    shared_time_policy: String,
+    #[nserde(default)]
+    in_shared_turn: bool,
+    #[nserde(default)]
+    shared_turn_indices: Vec<usize>,
+    /// Empty if not in a phase.
+    #[nserde(default)]
+    phase: String,
+    /// Parallel to [GAME_PHASES], or empty if saved before there were phases.
+    #[nserde(default)]
+    phase_ms: Vec<u64>,
+}
+
+#[derive(SerJson, DeJson)]
+struct PlayerSnapshot {
+    name: String,
+    color: String,
+    /// Empty for no team.
+    #[nserde(default)]
+    team: String,
+    total_ms: u64,
+    #[nserde(default)]
+    shared_ms: u64,
+    current_turn_ms: u64,
+    completed_turns_ms: Vec<u64>,
+    /// Ran out of banked time. False in sessions saved before flags were kept.
+    #[nserde(default)]
+    flagged: bool,
+}
+
+#[derive(SerJson, DeJson)]
+struct TurnChangeSnapshot {
+    from_index: usize,
+    to_index: usize,
+    to_round: usize,
+    ended_turn_ms: u64,
+    counted: bool,
+}
+
+impl SessionSnapshot {
+    pub(crate) fn capture(tracker: &TurnTimeTracker) -> Self {
+        let (players, current_index) = tracker.players.raw();
+        Self {
+            game_name: tracker.game_name.clone(),
+            players: players
+                .iter()
+                .map(|player| PlayerSnapshot {
+                    name: player.display_name.clone(),
+                    color: bq::color_to_hex_str(player.display_color),
+                    team: player.team.clone().unwrap_or_default(),
+                    total_ms: to_ms(player.total_time),
+                    shared_ms: to_ms(player.shared_time),
+                    current_turn_ms: to_ms(player.stats.current_turn_duration),
+                    completed_turns_ms: player
+                        .stats
+                        .completed_turn_durations
+                        .iter()
+                        .map(|d| to_ms(*d))
+                        .collect(),
+                    flagged: player.flagged,
+                })
+                .collect(),
+            current_index,
+            turn_log: tracker
+                .turn_log
+                .changes()
+                .iter()
+                .map(|change| TurnChangeSnapshot {
+                    from_index: change.from_index,
+                    to_index: change.to_index,
+                    to_round: change.to_round,
+                    ended_turn_ms: to_ms(change.ended_turn.duration),
+                    counted: change.ended_turn.counted,
+                })
+                .collect(),
+            paused: matches!(tracker.timer, TimerState::Paused),
+            time_limits: tracker.time_limits.to_json(),
+            shared_time_policy: tracker.shared_time_policy.label().to_string(),
+            in_shared_turn: tracker.opt_shared_turn.is_some(),
+            shared_turn_indices: tracker
+                .opt_shared_turn
+                .as_ref()
+                .map(|shared_turn| shared_turn.active_indices().to_vec())
+                .unwrap_or_default(),
+            phase: tracker
+                .opt_phase
+                .map_or("", |phase| phase.label())
+                .to_string(),
+            phase_ms: tracker
+                .phase_times
+                .iter()
+                .map(|(_, time)| to_ms(time))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> String {
+        self.serialize_json()
+    }
+
+    /// Parse and check the snapshot is consistent enough to resume.
+    pub(crate) fn parse(json: &str) -> Result<Self, String> {
+        let snapshot =
+            Self::deserialize_json(json).map_err(|e| format!("invalid saved session ({e})"))?;
+
+        let roster_players = snapshot
+            .players
+            .iter()
+            .map(|p| {
+                let opt_team = (!p.team.is_empty()).then(|| p.team.clone());
+                RosterPlayer::parse(&format!("{}:{}", p.name, p.color))
+                    .map(|roster_player| roster_player.with_team(opt_team))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        roster::validate(&roster_players)?;
+        TimeLimits::from_json(&snapshot.time_limits)?;
+        SharedTimePolicy::parse(&snapshot.shared_time_policy)?;
+        if !snapshot.phase.is_empty() {
+            GamePhase::parse(&snapshot.phase)?;
+        }
+        if !snapshot.phase_ms.is_empty() && snapshot.phase_ms.len() != GAME_PHASES.len() {
+            return Err(format!(
+                "expected {} phase times, not {}",
+                GAME_PHASES.len(),
+                snapshot.phase_ms.len()
+            ));
+        }
+
+        let num_players = snapshot.players.len();
+        if snapshot.current_index >= num_players {
+            return Err(format!(
+                "current player {} is out of range",
+                snapshot.current_index
+            ));
+        }
+        let mut expected_from_index = None;
+        for change in &snapshot.turn_log {
+            if change.from_index >= num_players || change.to_index >= num_players {
+                return Err("turn log refers to a missing player".to_string());
+            }
+            if expected_from_index.is_some_and(|index| index != change.from_index) {
+                return Err("turn log is out of order".to_string());
+            }
+            if change.to_round == 0 {
+                return Err("rounds start at 1".to_string());
+            }
+            expected_from_index = Some(change.to_index);
+        }
+        if expected_from_index.is_some_and(|index| index != snapshot.current_index) {
+            return Err("turn log doesn't end at the current player".to_string());
+        }
+        if snapshot
+            .shared_turn_indices
+            .iter()
+            .any(|index| *index >= num_players)
+        {
+            return Err("shared turn refers to a missing player".to_string());
+        }
+        if snapshot.players.iter().any(|p| p.shared_ms > p.total_ms) {
+            return Err("a player's shared time is more than their total time".to_string());
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Short description to offer resuming with, e.g. `Catan, 4 players, 01:02:03`.
+    pub(crate) fn summary(&self) -> String {
+        let total_time = self
+            .players
+            .iter()
+            .map(|p| Duration::from_millis(p.total_ms))
+            .sum();
+        let game_prefix = if self.game_name.is_empty() {
+            String::new()
+        } else {
+            format!("{}, ", self.game_name)
+        };
+        format!(
+            "{game_prefix}{} players, {}",
+            self.players.len(),
+            format_duration_concise(total_time)
+        )
+    }
+
+    /// A running session resumes running from `now`; time while the app was closed isn't counted.
+    pub(crate) fn into_tracker(self, now: Timestamp) -> TurnTimeTracker {
+        let players = self
+            .players
+            .into_iter()
+            .map(|p| {
+                let color = bq::color_from_hex_str(&p.color).unwrap_or(mq::WHITE);
+                Player {
+                    team: (!p.team.is_empty()).then_some(p.team),
+                    total_time: Duration::from_millis(p.total_ms),
+                    shared_time: Duration::from_millis(p.shared_ms),
+                    stats: PlayerTurnDurationStats {
+                        current_turn_duration: Duration::from_millis(p.current_turn_ms),
+                        completed_turn_durations: p
+                            .completed_turns_ms
+                            .into_iter()
+                            .map(Duration::from_millis)
+                            .collect::<BinaryHeap<_>>(),
+                    },
+                    flagged: p.flagged,
+                    ..Player::new(p.name, color)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut turn_log = TurnLog::new();
+        for change in self.turn_log {
+            turn_log.record(TurnChange {
+                from_index: change.from_index,
+                to_index: change.to_index,
+                to_round: change.to_round,
+                ended_turn: EndedTurn {
+                    duration: Duration::from_millis(change.ended_turn_ms),
+                    counted: change.counted,
+                },
+            });
+        }
+
+        let mut players = InfiniteIterator::from(players);
+        players.set_current_index(self.current_index);
+
+        let timer = if self.paused {
+            TimerState::Paused
+        } else {
+            TimerState::Running { last_tick: now }
+        };
+        // Checked by `parse()`.
+        let mut tracker = TurnTimeTracker::from_state(players, turn_log, timer)
+            .with_time_limits(TimeLimits::from_json(&self.time_limits).unwrap_or_default())
+            .with_game_name(self.game_name);
+        tracker.shared_time_policy =
+            SharedTimePolicy::parse(&self.shared_time_policy).unwrap_or_default();
+        // Sessions saved before the last active player was kept could have nobody active.
+        tracker.opt_shared_turn = (self.in_shared_turn && !self.shared_turn_indices.is_empty())
+            .then(|| SharedTurn::new(self.shared_turn_indices));
+        tracker.opt_phase = GamePhase::parse(&self.phase).ok();
+        let mut phase_totals = [Duration::ZERO; GAME_PHASES.len()];
+        for (total, ms) in phase_totals.iter_mut().zip(self.phase_ms) {
+            *total = Duration::from_millis(ms);
+        }
+        tracker.phase_times = PhaseTimes::from_totals(phase_totals);
+        tracker
+    }
+}
+
+fn to_ms(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+/// The last autosaved session, if there's one that can be resumed.
+pub(crate) fn load_saved() -> Option<SessionSnapshot> {
+    let json = bq::storage_load(SAVED_SESSION_KEY)?;
+    match SessionSnapshot::parse(&json) {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            mq::warn!("Ignoring saved session: {e}");
+            None
+        }
+    }
+}
+
+pub(crate) fn save(tracker: &TurnTimeTracker) {
+    let json = SessionSnapshot::capture(tracker).to_json();
+    if let Err(e) = bq::storage_store(SAVED_SESSION_KEY, &json) {
+        mq::warn!("Failed to save session: {e}");
+    }
+}
+
+/// Forget the saved session, once it's finished.
+pub(crate) fn clear_saved() {
+    bq::storage_remove(SAVED_SESSION_KEY);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SessionSnapshot;
+    use crate::phases::GamePhase;
+    use crate::roster::RosterPlayer;
+    use crate::shared_turn::SharedTimePolicy;
+    use crate::{TurnDirection, TurnTimeTracker};
+    use better_quad::bq::Timestamp;
+    use macroquad::prelude as mq;
+    use std::time::Duration;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut tracker = TurnTimeTracker::from_roster(vec![
+            RosterPlayer::new("A", mq::RED).with_team(Some("Red".to_string())),
+            RosterPlayer::new("B", mq::BLUE),
+        ])
+        .with_game_name("Catan".to_string());
+        tracker.tick_current_player(Duration::from_millis(10_250));
+        tracker.change_player(1, TurnDirection::Forward);
+        tracker.tick_current_player(Duration::from_secs(3));
+        tracker.start_team_turn(Timestamp::now()); // B isn't on a team
+        tracker.toggle_shared_turn();
+        tracker.jump_to_player(0);
+        tracker.shared_time_policy = SharedTimePolicy::Duplicate;
+        tracker.opt_phase = Some(GamePhase::Rules);
+        tracker.tick_current_player(Duration::from_secs(30));
+        tracker.opt_phase = Some(GamePhase::Break);
+
+        let json = SessionSnapshot::capture(&tracker).to_json();
+        let mut resumed = SessionSnapshot::parse(&json)
+            .unwrap()
+            .into_tracker(Timestamp::now());
+        assert_eq!(
+            "Catan, 2 players, 00:00:13",
+            SessionSnapshot::parse(&json).unwrap().summary()
+        );
+        assert_eq!("Catan", resumed.game_name);
+        assert_eq!(Some("Red"), resumed.players.raw().0[0].team.as_deref());
+        assert_eq!(SharedTimePolicy::Duplicate, resumed.shared_time_policy);
+        assert_eq!(Some(GamePhase::Break), resumed.opt_phase);
+        assert_eq!(Duration::from_secs(30), resumed.phase_times.total());
+        resumed.end_phase();
+        resumed.tick_current_player(Duration::from_secs(2));
+        let players = resumed.players.raw().0;
+        assert_eq!(Duration::from_secs(2), players[0].shared_time);
+        assert_eq!(Duration::from_secs(5), players[1].total_time);
+        resumed.end_shared_turn();
+
+        // The turn log survives too, so changes from before saving can still be undone.
+        resumed.undo_player_change();
+        let (players, current_index) = resumed.players.raw();
+        assert_eq!(0, current_index);
+        assert_eq!("A", players[0].display_name);
+        assert_eq!(Duration::from_millis(15_250), players[0].total_time);
+        assert_eq!(0, players[0].stats.completed_turn_durations.len());
+        assert_eq!(Duration::from_secs(2), players[1].total_time);
+    }
+
+    #[test]
+    fn test_parse_rejects_inconsistent_snapshot() {
+        let tracker = TurnTimeTracker::with_players(vec![("A", mq::RED), ("B", mq::BLUE)]);
+        let json = SessionSnapshot::capture(&tracker).to_json();
+        assert!(SessionSnapshot::parse(&json).is_ok());
+
+        let bad_index = json.replace("\"current_index\":0", "\"current_index\":2");
+        assert_eq!(
+            Err("current player 2 is out of range".to_string()),
+            SessionSnapshot::parse(&bad_index).map(|_| ())
+        );
+        assert!(SessionSnapshot::parse("{\"players\": []}").is_err());
+    }
+
+    #[test]
+    fn test_parse_session_from_before_time_limits() {
+        let json = r##"{
+            "players": [
+                {"name": "A", "color": "#ff0000", "total_ms": 5000, "current_turn_ms": 5000,
+                    "completed_turns_ms": []},
+                {"name": "B", "color": "#0000ff", "total_ms": 0, "current_turn_ms": 0,
+                    "completed_turns_ms": []}
+            ],
+            "current_index": 0,
+            "turn_log": [],
+            "paused": true
+        }"##;
+        let snapshot = SessionSnapshot::parse(json).unwrap();
+        assert_eq!("2 players, 00:00:05", snapshot.summary());
+    }
+}
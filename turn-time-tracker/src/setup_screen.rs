@@ -0,0 +1,593 @@
+//! Screen to pick players, their colors, and turn order before starting the tracker.
+use crate::archive::{self, MAX_GAME_NAME_LEN};
+use crate::roster::{self, RosterPlayer, MAX_NAME_LEN};
+use crate::time_limits::{
+    self, TimeLimits, CLOCK_MODE_OPTIONS, TIME_BANK_OPTIONS, TURN_LIMIT_OPTIONS,
+};
+use crate::{format_duration_stats, next_option};
+use better_quad::bq::{
+    self, BetterKeyCode, SimpleButton, TextAlignment, TextAnchorPoint, TextBackground,
+};
+use macroquad::prelude as mq;
+
+// Control consts
+const KEY_START: mq::KeyCode = mq::KeyCode::Enter;
+const KEY_SELECT_NEXT_ROW: mq::KeyCode = mq::KeyCode::Tab;
+const KEY_DELETE_CHAR: mq::KeyCode = mq::KeyCode::Backspace;
+
+// Fits the tracker's default window height.
+const MAX_PLAYERS: usize = 10;
+
+/// Clicking a player's color swatch cycles through these.
+const PLAYER_COLOR_PALETTE: [mq::Color; 14] = [
+    mq::YELLOW,
+    mq::SKYBLUE,
+    mq::PINK,
+    mq::GREEN,
+    mq::DARKBLUE,
+    mq::ORANGE,
+    mq::DARKBROWN,
+    mq::RED,
+    mq::PURPLE,
+    mq::LIME,
+    mq::MAROON,
+    mq::BEIGE,
+    mq::MAGENTA,
+    mq::DARKGREEN,
+];
+
+/// Clicking a player's team button cycles through these, with `None` for no team. Other team names
+/// can be given in roster files.
+const TEAM_OPTIONS: [Option<&str>; 5] =
+    [None, Some("Red"), Some("Blue"), Some("Green"), Some("Gold")];
+
+// Draw consts
+const SETUP_X: f32 = 20.0;
+const TITLE_FONT_SIZE: u16 = 40;
+const ROWS_Y: f32 = 80.0;
+const ROW_HEIGHT: f32 = 50.0;
+const ROW_PADDING: f32 = 8.0;
+const SWATCH_SIZE: f32 = ROW_HEIGHT - ROW_PADDING;
+const NAME_BOX_WIDTH: f32 = 220.0;
+const GAME_NAME_X: f32 = 170.0;
+const LEADERBOARD_BUTTON_WIDTH: f32 = 180.0;
+const TEAM_BUTTON_WIDTH: f32 = 110.0;
+const SMALL_BUTTON_WIDTH: f32 = 40.0;
+const BUTTON_FONT_SIZE: u16 = 30;
+const BUTTON_BORDER_THICKNESS: f32 = 2.0;
+const BIG_BUTTON_WIDTH: f32 = 200.0;
+const BIG_BUTTON_HEIGHT: f32 = 50.0;
+const RESUME_BUTTON_WIDTH: f32 = 280.0;
+const LIMIT_BUTTON_WIDTH: f32 = 180.0;
+const SELECTED_ROW_THICKNESS: f32 = 4.0;
+const HELP_FONT_SIZE: u16 = 22;
+
+pub(crate) struct SetupScreen {
+    /// Empty if not given.
+    game_name: String,
+    players: Vec<RosterPlayer>,
+    opt_selected_field: Option<TextField>,
+    // Rebuilt whenever the rows move. Parallel to `players`.
+    row_buttons: Vec<RowButtons>,
+    game_name_button: SimpleButton,
+    leaderboard_button: SimpleButton,
+    add_button: SimpleButton,
+    time_limits: TimeLimits,
+    soft_limit_button: SimpleButton,
+    hard_limit_button: SimpleButton,
+    time_bank_button: SimpleButton,
+    // Only shown with a time bank.
+    clock_mode_button: SimpleButton,
+    skip_flagged_button: SimpleButton,
+    start_button: SimpleButton,
+    // Only shown if there's a saved session to resume.
+    resume_button: SimpleButton,
+    resume_text: Option<String>,
+    error_text: Option<String>,
+}
+
+/// Field receiving typed characters.
+#[derive(Copy, Clone, PartialEq)]
+enum TextField {
+    GameName,
+    PlayerName(usize),
+}
+
+struct RowButtons {
+    swatch: SimpleButton,
+    name: SimpleButton,
+    up: SimpleButton,
+    down: SimpleButton,
+    remove: SimpleButton,
+    team: SimpleButton,
+}
+
+enum RowAction {
+    CycleColor,
+    CycleTeam,
+    Select,
+    MoveUp,
+    MoveDown,
+    Remove,
+}
+
+/// Result of a frame of the setup screen.
+pub(crate) enum SetupOutcome {
+    Editing,
+    /// Players, time limits, and game name.
+    Start(Vec<RosterPlayer>, TimeLimits, String),
+    /// Resume the last saved session instead of starting a new one.
+    Resume,
+    ShowLeaderboard,
+}
+
+impl SetupScreen {
+    /// Starts with the last game name, roster, and time limits used, or `default_players` and no
+    /// limits if there aren't any.
+    pub(crate) fn new(default_players: Vec<RosterPlayer>) -> Self {
+        let players = roster::load_saved().unwrap_or(default_players);
+        let mut setup_screen = Self {
+            game_name: archive::load_saved_game_name(),
+            players,
+            opt_selected_field: None,
+            row_buttons: Vec::new(),
+            game_name_button: SimpleButton::new(0.0, 0.0, 0.0, 0.0),
+            leaderboard_button: SimpleButton::new(0.0, 0.0, 0.0, 0.0),
+            add_button: SimpleButton::new(0.0, 0.0, 0.0, 0.0),
+            time_limits: time_limits::load_saved(),
+            soft_limit_button: SimpleButton::new(0.0, 0.0, 0.0, 0.0),
+            hard_limit_button: SimpleButton::new(0.0, 0.0, 0.0, 0.0),
+            time_bank_button: SimpleButton::new(0.0, 0.0, 0.0, 0.0),
+            clock_mode_button: SimpleButton::new(0.0, 0.0, 0.0, 0.0),
+            skip_flagged_button: SimpleButton::new(0.0, 0.0, 0.0, 0.0),
+            start_button: SimpleButton::new(0.0, 0.0, 0.0, 0.0),
+            resume_button: SimpleButton::new(0.0, 0.0, 0.0, 0.0),
+            resume_text: None,
+            error_text: None,
+        };
+        setup_screen.layout_buttons();
+        setup_screen
+    }
+
+    pub(crate) fn set_error_text(&mut self, error_text: String) {
+        self.error_text = Some(error_text);
+    }
+
+    /// Offer to resume a saved session, described by `resume_text`.
+    pub(crate) fn set_resume_text(&mut self, resume_text: String) {
+        self.resume_text = Some(resume_text);
+    }
+
+    fn row_y(i: usize) -> f32 {
+        ROWS_Y + ROW_HEIGHT * i as f32
+    }
+
+    fn layout_buttons(&mut self) {
+        let title_row_y = (ROWS_Y - ROW_HEIGHT) / 2.0 + ROW_PADDING / 2.0;
+        self.game_name_button = SimpleButton::new(
+            GAME_NAME_X,
+            title_row_y,
+            NAME_BOX_WIDTH,
+            ROW_HEIGHT - ROW_PADDING,
+        );
+        self.leaderboard_button = SimpleButton::new(
+            GAME_NAME_X + NAME_BOX_WIDTH + ROW_PADDING,
+            title_row_y,
+            LEADERBOARD_BUTTON_WIDTH,
+            ROW_HEIGHT - ROW_PADDING,
+        );
+        self.row_buttons = (0..self.players.len())
+            .map(|i| {
+                let y = Self::row_y(i) + ROW_PADDING / 2.0;
+                let height = ROW_HEIGHT - ROW_PADDING;
+                let name_x = SETUP_X + SWATCH_SIZE + ROW_PADDING;
+                let up_x = name_x + NAME_BOX_WIDTH + ROW_PADDING;
+                let down_x = up_x + SMALL_BUTTON_WIDTH + ROW_PADDING;
+                let remove_x = down_x + SMALL_BUTTON_WIDTH + ROW_PADDING;
+                let team_x = remove_x + SMALL_BUTTON_WIDTH + ROW_PADDING;
+                RowButtons {
+                    swatch: SimpleButton::new(SETUP_X, y, SWATCH_SIZE, height),
+                    name: SimpleButton::new(name_x, y, NAME_BOX_WIDTH, height),
+                    up: SimpleButton::new(up_x, y, SMALL_BUTTON_WIDTH, height),
+                    down: SimpleButton::new(down_x, y, SMALL_BUTTON_WIDTH, height),
+                    remove: SimpleButton::new(remove_x, y, SMALL_BUTTON_WIDTH, height),
+                    team: SimpleButton::new(team_x, y, TEAM_BUTTON_WIDTH, height),
+                }
+            })
+            .collect();
+
+        let below_rows_y = Self::row_y(self.players.len()) + ROW_PADDING;
+        self.add_button =
+            SimpleButton::new(SETUP_X, below_rows_y, BIG_BUTTON_WIDTH, BIG_BUTTON_HEIGHT);
+        let limits_y = Self::row_y(MAX_PLAYERS + 1) + ROW_PADDING;
+        let [soft_limit_x, hard_limit_x, time_bank_x] =
+            [0.0, 1.0, 2.0].map(|i| SETUP_X + (LIMIT_BUTTON_WIDTH + ROW_PADDING) * i);
+        self.soft_limit_button = SimpleButton::new(
+            soft_limit_x,
+            limits_y,
+            LIMIT_BUTTON_WIDTH,
+            BIG_BUTTON_HEIGHT,
+        );
+        self.hard_limit_button = SimpleButton::new(
+            hard_limit_x,
+            limits_y,
+            LIMIT_BUTTON_WIDTH,
+            BIG_BUTTON_HEIGHT,
+        );
+        self.time_bank_button =
+            SimpleButton::new(time_bank_x, limits_y, LIMIT_BUTTON_WIDTH, BIG_BUTTON_HEIGHT);
+        let bank_options_y = Self::row_y(MAX_PLAYERS + 2) + ROW_PADDING;
+        self.clock_mode_button = SimpleButton::new(
+            soft_limit_x,
+            bank_options_y,
+            time_bank_x - soft_limit_x - ROW_PADDING,
+            BIG_BUTTON_HEIGHT,
+        );
+        self.skip_flagged_button = SimpleButton::new(
+            time_bank_x,
+            bank_options_y,
+            LIMIT_BUTTON_WIDTH,
+            BIG_BUTTON_HEIGHT,
+        );
+
+        let start_y = Self::row_y(MAX_PLAYERS + 3) + ROW_PADDING;
+        self.start_button =
+            SimpleButton::new(SETUP_X, start_y, BIG_BUTTON_WIDTH, BIG_BUTTON_HEIGHT);
+        self.resume_button = SimpleButton::new(
+            SETUP_X + BIG_BUTTON_WIDTH + ROW_PADDING,
+            start_y,
+            RESUME_BUTTON_WIDTH,
+            BIG_BUTTON_HEIGHT,
+        );
+    }
+
+    pub(crate) fn update(&mut self) -> SetupOutcome {
+        // Unconditionally tick all buttons so their press state stays in sync with the mouse.
+        let mut opt_row_action = None;
+        for (i, buttons) in self.row_buttons.iter_mut().enumerate() {
+            let actions = [
+                (buttons.swatch.tick_state(), RowAction::CycleColor),
+                (buttons.name.tick_state(), RowAction::Select),
+                (buttons.up.tick_state(), RowAction::MoveUp),
+                (buttons.down.tick_state(), RowAction::MoveDown),
+                (buttons.remove.tick_state(), RowAction::Remove),
+                (buttons.team.tick_state(), RowAction::CycleTeam),
+            ];
+            for (button_action, row_action) in actions {
+                if button_action.should_trigger_action() {
+                    opt_row_action = Some((i, row_action));
+                }
+            }
+        }
+        let game_name_clicked = self.game_name_button.tick_state().should_trigger_action();
+        let leaderboard_clicked = self.leaderboard_button.tick_state().should_trigger_action();
+        let add_clicked = self.add_button.tick_state().should_trigger_action();
+        let soft_limit_clicked = self.soft_limit_button.tick_state().should_trigger_action();
+        let hard_limit_clicked = self.hard_limit_button.tick_state().should_trigger_action();
+        let time_bank_clicked = self.time_bank_button.tick_state().should_trigger_action();
+        let clock_mode_clicked = self.clock_mode_button.tick_state().should_trigger_action();
+        let skip_flagged_clicked = self
+            .skip_flagged_button
+            .tick_state()
+            .should_trigger_action();
+        let start_clicked = self.start_button.tick_state().should_trigger_action();
+        let resume_clicked = self.resume_button.tick_state().should_trigger_action();
+
+        if resume_clicked && self.resume_text.is_some() {
+            return SetupOutcome::Resume;
+        }
+        if leaderboard_clicked {
+            return SetupOutcome::ShowLeaderboard;
+        }
+
+        if game_name_clicked {
+            self.opt_selected_field = Some(TextField::GameName);
+        }
+        if let Some((i, row_action)) = opt_row_action {
+            self.apply_row_action(i, row_action);
+        }
+
+        if add_clicked && self.players.len() < MAX_PLAYERS {
+            self.players
+                .push(RosterPlayer::new("", self.unused_color()));
+            self.opt_selected_field = Some(TextField::PlayerName(self.players.len() - 1));
+            self.layout_buttons();
+        }
+
+        let limits = &mut self.time_limits;
+        if soft_limit_clicked {
+            limits.soft_limit = next_option(&TURN_LIMIT_OPTIONS, limits.soft_limit);
+        }
+        if hard_limit_clicked {
+            limits.hard_limit = next_option(&TURN_LIMIT_OPTIONS, limits.hard_limit);
+        }
+        if time_bank_clicked {
+            limits.time_bank = next_option(&TIME_BANK_OPTIONS, limits.time_bank);
+        }
+        if limits.time_bank.is_some() {
+            if clock_mode_clicked {
+                limits.clock_mode = next_option(&CLOCK_MODE_OPTIONS, limits.clock_mode);
+            }
+            if skip_flagged_clicked {
+                limits.skip_flagged_players = !limits.skip_flagged_players;
+            }
+        }
+
+        self.apply_typing();
+
+        if start_clicked || mq::is_key_pressed(KEY_START) {
+            self.game_name = self.game_name.trim().to_string();
+            for player in &mut self.players {
+                player.name = player.name.trim().to_string();
+            }
+            match roster::validate(&self.players) {
+                Ok(()) => {
+                    archive::save_game_name(&self.game_name);
+                    roster::save(&self.players);
+                    time_limits::save(self.time_limits);
+                    return SetupOutcome::Start(
+                        self.players.clone(),
+                        self.time_limits,
+                        self.game_name.clone(),
+                    );
+                }
+                Err(e) => self.error_text = Some(e),
+            }
+        }
+
+        SetupOutcome::Editing
+    }
+
+    fn apply_row_action(&mut self, i: usize, row_action: RowAction) {
+        match row_action {
+            RowAction::CycleColor => {
+                let palette_index = PLAYER_COLOR_PALETTE
+                    .iter()
+                    .position(|c| same_color(*c, self.players[i].color))
+                    .map_or(0, |index| (index + 1) % PLAYER_COLOR_PALETTE.len());
+                self.players[i].color = PLAYER_COLOR_PALETTE[palette_index];
+            }
+            RowAction::CycleTeam => {
+                let team = &mut self.players[i].team;
+                let team_index = TEAM_OPTIONS
+                    .iter()
+                    .position(|option| *option == team.as_deref())
+                    .map_or(0, |index| (index + 1) % TEAM_OPTIONS.len());
+                *team = TEAM_OPTIONS[team_index].map(str::to_string);
+            }
+            RowAction::Select => self.opt_selected_field = Some(TextField::PlayerName(i)),
+            RowAction::MoveUp => {
+                if i > 0 {
+                    self.players.swap(i, i - 1);
+                    self.opt_selected_field = Some(TextField::PlayerName(i - 1));
+                }
+            }
+            RowAction::MoveDown => {
+                if i + 1 < self.players.len() {
+                    self.players.swap(i, i + 1);
+                    self.opt_selected_field = Some(TextField::PlayerName(i + 1));
+                }
+            }
+            RowAction::Remove => {
+                self.players.remove(i);
+                self.opt_selected_field = None;
+                self.layout_buttons();
+            }
+        }
+    }
+
+    fn unused_color(&self) -> mq::Color {
+        PLAYER_COLOR_PALETTE
+            .into_iter()
+            .find(|c| !self.players.iter().any(|p| same_color(p.color, *c)))
+            .unwrap_or(PLAYER_COLOR_PALETTE[0])
+    }
+
+    fn apply_typing(&mut self) {
+        if mq::is_key_pressed(KEY_SELECT_NEXT_ROW) {
+            // Through the player names, then the game name.
+            self.opt_selected_field = Some(match self.opt_selected_field {
+                Some(TextField::PlayerName(i)) if i + 1 < self.players.len() => {
+                    TextField::PlayerName(i + 1)
+                }
+                Some(TextField::PlayerName(_)) => TextField::GameName,
+                None | Some(TextField::GameName) if !self.players.is_empty() => {
+                    TextField::PlayerName(0)
+                }
+                None | Some(TextField::GameName) => TextField::GameName,
+            });
+        }
+
+        // Always drain typed chars, so they don't show up once a field is selected.
+        let typed_chars = std::iter::from_fn(mq::get_char_pressed).collect::<Vec<_>>();
+        let (name, max_len) = match self.opt_selected_field {
+            None => return,
+            Some(TextField::GameName) => (&mut self.game_name, MAX_GAME_NAME_LEN),
+            Some(TextField::PlayerName(i)) => (&mut self.players[i].name, MAX_NAME_LEN),
+        };
+
+        if mq::is_key_pressed(KEY_DELETE_CHAR) {
+            name.pop();
+        }
+        for c in typed_chars {
+            if !c.is_control() && name.chars().count() < max_len {
+                name.push(c);
+            }
+        }
+    }
+
+    pub(crate) fn draw(&self) {
+        mq::clear_background(mq::LIGHTGRAY);
+
+        bq::draw_text(
+            "Players",
+            TextAlignment::Left,
+            None,
+            TITLE_FONT_SIZE,
+            mq::BLACK,
+            TextAnchorPoint::TopLeft {
+                x: SETUP_X,
+                y: ROWS_Y / 2.0 - TITLE_FONT_SIZE as f32 / 2.0,
+            },
+            None,
+        );
+
+        draw_text_field(
+            &self.game_name_button,
+            &self.game_name,
+            "game name",
+            self.opt_selected_field == Some(TextField::GameName),
+        );
+        draw_button(&self.leaderboard_button, "Leaderboard");
+
+        for (i, (player, buttons)) in self.players.iter().zip(&self.row_buttons).enumerate() {
+            let swatch_y = Self::row_y(i) + ROW_PADDING / 2.0;
+            mq::draw_rectangle(SETUP_X, swatch_y, SWATCH_SIZE, SWATCH_SIZE, player.color);
+            mq::draw_rectangle_lines(
+                SETUP_X,
+                swatch_y,
+                SWATCH_SIZE,
+                SWATCH_SIZE,
+                BUTTON_BORDER_THICKNESS,
+                mq::BLACK,
+            );
+            let is_selected = self.opt_selected_field == Some(TextField::PlayerName(i));
+            draw_text_field(&buttons.name, &player.name, "", is_selected);
+            for (button, text) in [
+                (&buttons.up, "^"),
+                (&buttons.down, "v"),
+                (&buttons.remove, "x"),
+                (&buttons.team, player.team.as_deref().unwrap_or("no team")),
+            ] {
+                draw_button(button, text);
+            }
+        }
+
+        if self.players.len() < MAX_PLAYERS {
+            draw_button(&self.add_button, "+ Add player");
+        }
+        let limit_text = |label: &str, opt_limit: Option<std::time::Duration>| match opt_limit {
+            Some(limit) => format!("{label}: {}", format_duration_stats(Some(limit))),
+            None => format!("{label}: off"),
+        };
+        let limits = &self.time_limits;
+        draw_button(
+            &self.soft_limit_button,
+            &limit_text("Soft", limits.soft_limit),
+        );
+        draw_button(
+            &self.hard_limit_button,
+            &limit_text("Hard", limits.hard_limit),
+        );
+        draw_button(
+            &self.time_bank_button,
+            &limit_text("Bank", limits.time_bank),
+        );
+        if limits.time_bank.is_some() {
+            draw_button(
+                &self.clock_mode_button,
+                &format!("Clock: {}", limits.clock_mode.label()),
+            );
+            draw_button(
+                &self.skip_flagged_button,
+                if limits.skip_flagged_players {
+                    "Flagged: skip"
+                } else {
+                    "Flagged: play"
+                },
+            );
+        }
+
+        draw_button(&self.start_button, "Start");
+        if self.resume_text.is_some() {
+            draw_button(&self.resume_button, "Resume last");
+        }
+
+        let mut help_text = format!(
+            "Click a name or the game to edit it, [{}] to edit the next.\n\
+            Click a color to change it, or a team button to pick a team.\n\
+            Soft/hard limits warn when a turn runs long.\n\
+            Bank gives each player a total time that counts down.\n\
+            Players who run out are flagged, and can be skipped.\n\
+            Press [{}] or click Start when ready.",
+            KEY_SELECT_NEXT_ROW.to_lowercase(),
+            KEY_START.to_lowercase(),
+        );
+        if let Some(resume_text) = &self.resume_text {
+            help_text += &format!("\nResume last continues the saved session ({resume_text}).");
+        }
+        let help_text_container = bq::draw_text(
+            help_text,
+            TextAlignment::Left,
+            None,
+            HELP_FONT_SIZE,
+            mq::DARKGRAY,
+            TextAnchorPoint::TopLeft {
+                x: SETUP_X,
+                y: Self::row_y(MAX_PLAYERS + 4) + ROW_PADDING * 2.0,
+            },
+            None,
+        );
+
+        if let Some(error_text) = &self.error_text {
+            bq::draw_text(
+                error_text,
+                TextAlignment::Left,
+                None,
+                HELP_FONT_SIZE,
+                mq::WHITE,
+                TextAnchorPoint::TopLeft {
+                    x: SETUP_X,
+                    y: help_text_container.rect_y + help_text_container.rect_height + ROW_PADDING,
+                },
+                Some(TextBackground {
+                    color: mq::RED,
+                    x_padding: 4.0,
+                    y_padding: 4.0,
+                }),
+            );
+        }
+    }
+}
+
+/// Compare at hex precision, since saved rosters round-trip colors through hex.
+fn same_color(a: mq::Color, b: mq::Color) -> bool {
+    bq::color_to_hex_str(a) == bq::color_to_hex_str(b)
+}
+
+/// `placeholder` is shown in grey while `text` is empty and not selected.
+fn draw_text_field(button: &SimpleButton, text: &str, placeholder: &str, is_selected: bool) {
+    let (shown_text, text_color) = match (is_selected, text.is_empty()) {
+        (true, _) => (format!("{text}_"), mq::BLACK),
+        (false, true) => (placeholder.to_string(), mq::GRAY),
+        (false, false) => (text.to_string(), mq::BLACK),
+    };
+    button.draw(
+        mq::WHITE,
+        if is_selected { mq::GOLD } else { mq::BLACK },
+        if is_selected {
+            SELECTED_ROW_THICKNESS
+        } else {
+            BUTTON_BORDER_THICKNESS
+        },
+        shown_text,
+        BUTTON_FONT_SIZE,
+        text_color,
+    );
+}
+
+fn draw_button(button: &SimpleButton, text: &str) {
+    let btn_color = if button.is_pressed() {
+        mq::GRAY
+    } else {
+        mq::WHITE
+    };
+    button.draw(
+        btn_color,
+        mq::BLACK,
+        BUTTON_BORDER_THICKNESS,
+        text,
+        BUTTON_FONT_SIZE,
+        mq::BLACK,
+    );
+}
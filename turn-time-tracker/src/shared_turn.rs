@@ -0,0 +1,208 @@
+//! Shared turns, where several players (e.g. a team, or everyone during a simultaneous phase) are
+//! active at once, and teams of players.
+use crate::Player;
+use macroquad::prelude as mq;
+use std::time::Duration;
+
+/// How time during a shared turn is charged to the active players.
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub(crate) enum SharedTimePolicy {
+    /// Each active player is charged an equal part of the time.
+    #[default]
+    Split,
+    /// Each active player is charged all of the time, as if it were their own turn.
+    Duplicate,
+}
+
+impl SharedTimePolicy {
+    pub(crate) fn toggled(self) -> Self {
+        match self {
+            Self::Split => Self::Duplicate,
+            Self::Duplicate => Self::Split,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Split => "split",
+            Self::Duplicate => "duplicated",
+        }
+    }
+
+    /// Parse a [Self::label], or the default if empty (saved before there were policies).
+    pub(crate) fn parse(label: &str) -> Result<Self, String> {
+        match label {
+            "" | "split" => Ok(Self::Split),
+            "duplicated" => Ok(Self::Duplicate),
+            _ => Err(format!("unknown shared time policy '{label}'")),
+        }
+    }
+}
+
+/// Players active at once. The regular current player's turn is on hold until it ends.
+pub(crate) struct SharedTurn {
+    /// Sorted player indices. Never empty, so the time is always charged to someone.
+    active_indices: Vec<usize>,
+}
+
+impl SharedTurn {
+    /// `active_indices` mustn't be empty.
+    pub(crate) fn new(mut active_indices: Vec<usize>) -> Self {
+        active_indices.sort_unstable();
+        active_indices.dedup();
+        Self { active_indices }
+    }
+
+    pub(crate) fn active_indices(&self) -> &[usize] {
+        &self.active_indices
+    }
+
+    pub(crate) fn is_active(&self, player_index: usize) -> bool {
+        self.active_indices.binary_search(&player_index).is_ok()
+    }
+
+    /// Add the player if they're not active, otherwise remove them, unless they're the last one.
+    pub(crate) fn toggle(&mut self, player_index: usize) {
+        match self.active_indices.binary_search(&player_index) {
+            Ok(_) if self.active_indices.len() == 1 => {}
+            Ok(i) => {
+                self.active_indices.remove(i);
+            }
+            Err(i) => self.active_indices.insert(i, player_index),
+        }
+    }
+
+    /// Time charged to each active player for `elapsed` time.
+    pub(crate) fn share(&self, elapsed: Duration, policy: SharedTimePolicy) -> Duration {
+        match policy {
+            SharedTimePolicy::Split => elapsed / self.active_indices.len() as u32,
+            SharedTimePolicy::Duplicate => elapsed,
+        }
+    }
+}
+
+pub(crate) struct Team {
+    pub(crate) name: String,
+    /// Average of the members' colors.
+    pub(crate) color: mq::Color,
+    /// In turn order.
+    pub(crate) member_indices: Vec<usize>,
+}
+
+impl Team {
+    pub(crate) fn total_time(&self, players: &[Player]) -> Duration {
+        self.member_indices
+            .iter()
+            .map(|i| players[*i].total_time)
+            .sum()
+    }
+}
+
+/// Teams in order of their first member's turn.
+pub(crate) fn teams(players: &[Player]) -> Vec<Team> {
+    let mut teams: Vec<Team> = Vec::new();
+    for (i, player) in players.iter().enumerate() {
+        let Some(team_name) = &player.team else {
+            continue;
+        };
+        match teams.iter_mut().find(|team| team.name == *team_name) {
+            Some(team) => team.member_indices.push(i),
+            None => teams.push(Team {
+                name: team_name.clone(),
+                color: mq::BLACK,
+                member_indices: vec![i],
+            }),
+        }
+    }
+    for team in &mut teams {
+        let num_members = team.member_indices.len() as f32;
+        let color_sum = team
+            .member_indices
+            .iter()
+            .map(|i| players[*i].display_color.to_vec())
+            .sum::<mq::Vec4>();
+        team.color = mq::Color::from_vec(color_sum / num_members);
+    }
+    teams
+}
+
+/// Player indices with each team's members next to each other, where the team's first member is.
+/// Players without a team keep their place.
+pub(crate) fn team_grouped_order(players: &[Player]) -> Vec<usize> {
+    let teams = teams(players);
+    let mut order = Vec::with_capacity(players.len());
+    for (i, player) in players.iter().enumerate() {
+        match &player.team {
+            None => order.push(i),
+            Some(team_name) => {
+                let team = teams
+                    .iter()
+                    .find(|team| team.name == *team_name)
+                    .expect("Every team name has a team");
+                if team.member_indices[0] == i {
+                    order.extend(&team.member_indices);
+                }
+            }
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{team_grouped_order, teams, SharedTimePolicy, SharedTurn};
+    use crate::Player;
+    use macroquad::prelude as mq;
+    use std::time::Duration;
+
+    #[test]
+    fn test_shared_turn() {
+        let mut shared_turn = SharedTurn::new(vec![2, 0]);
+        shared_turn.toggle(1);
+        assert_eq!(&[0, 1, 2], shared_turn.active_indices());
+        shared_turn.toggle(0);
+        assert!(!shared_turn.is_active(0));
+
+        let elapsed = Duration::from_secs(10);
+        assert_eq!(
+            Duration::from_secs(5),
+            shared_turn.share(elapsed, SharedTimePolicy::Split)
+        );
+        assert_eq!(
+            elapsed,
+            shared_turn.share(elapsed, SharedTimePolicy::Duplicate)
+        );
+
+        // The last active player stays active, so the time isn't charged to nobody.
+        shared_turn.toggle(1);
+        shared_turn.toggle(2);
+        assert_eq!(&[2], shared_turn.active_indices());
+        assert_eq!(elapsed, shared_turn.share(elapsed, SharedTimePolicy::Split));
+    }
+
+    #[test]
+    fn test_teams() {
+        let player = |name, team: Option<&str>| {
+            let mut player = Player::new(name, mq::WHITE);
+            player.team = team.map(str::to_string);
+            player
+        };
+        let players = [
+            player("A", Some("Red")),
+            player("B", Some("Blue")),
+            player("C", None),
+            player("D", Some("Red")),
+        ];
+
+        let teams = teams(&players);
+        let names_and_members = teams
+            .iter()
+            .map(|team| (team.name.as_str(), team.member_indices.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![("Red", vec![0, 3]), ("Blue", vec![1])],
+            names_and_members
+        );
+        assert_eq!(vec![0, 3, 1, 2], team_grouped_order(&players));
+    }
+}
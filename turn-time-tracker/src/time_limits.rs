@@ -0,0 +1,330 @@
+//! Optional time limits: a per-turn shot clock with a soft and hard limit, and chess-clock style
+//! time banks that count down each player's total time, optionally with an increment or delay.
+use better_quad::bq;
+use macroquad::prelude as mq;
+use nanoserde::{DeJson, SerJson};
+use std::time::Duration;
+
+const SAVED_TIME_LIMITS_KEY: &str = "turn-time-tracker-time-limits";
+
+/// Choices offered on the setup screen, in the order clicking cycles through them.
+pub(crate) const TURN_LIMIT_OPTIONS: [Option<Duration>; 8] = [
+    None,
+    Some(Duration::from_secs(30)),
+    Some(Duration::from_secs(60)),
+    Some(Duration::from_secs(90)),
+    Some(Duration::from_secs(2 * 60)),
+    Some(Duration::from_secs(3 * 60)),
+    Some(Duration::from_secs(5 * 60)),
+    Some(Duration::from_secs(10 * 60)),
+];
+pub(crate) const TIME_BANK_OPTIONS: [Option<Duration>; 8] = [
+    None,
+    Some(Duration::from_secs(5 * 60)),
+    Some(Duration::from_secs(10 * 60)),
+    Some(Duration::from_secs(15 * 60)),
+    Some(Duration::from_secs(20 * 60)),
+    Some(Duration::from_secs(30 * 60)),
+    Some(Duration::from_secs(45 * 60)),
+    Some(Duration::from_secs(60 * 60)),
+];
+pub(crate) const CLOCK_MODE_OPTIONS: [ClockMode; 8] = [
+    ClockMode::SuddenDeath,
+    ClockMode::Increment(Duration::from_secs(5)),
+    ClockMode::Increment(Duration::from_secs(10)),
+    ClockMode::Increment(Duration::from_secs(30)),
+    ClockMode::SimpleDelay(Duration::from_secs(5)),
+    ClockMode::SimpleDelay(Duration::from_secs(10)),
+    ClockMode::Bronstein(Duration::from_secs(5)),
+    ClockMode::Bronstein(Duration::from_secs(10)),
+];
+
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub(crate) struct TimeLimits {
+    /// Current player's highlight changes color once their turn is this long.
+    pub(crate) soft_limit: Option<Duration>,
+    /// Screen flashes once a turn is this long.
+    pub(crate) hard_limit: Option<Duration>,
+    /// Total time each player has for the whole game. Shown counting down, and the screen flashes
+    /// once the current player's bank is empty.
+    pub(crate) time_bank: Option<Duration>,
+    /// How the time bank is topped up. Only used with a time bank.
+    pub(crate) clock_mode: ClockMode,
+    /// Whether the next player key passes over players who ran out of banked time.
+    pub(crate) skip_flagged_players: bool,
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub(crate) enum ClockMode {
+    /// Banked time only goes down.
+    #[default]
+    SuddenDeath,
+    /// Fischer increment: this is added to the bank after each turn.
+    Increment(Duration),
+    /// The first part of each turn, up to this long, doesn't come out of the bank.
+    SimpleDelay(Duration),
+    /// The bank counts down from the start of the turn, then gets back the time used, up to this
+    /// long, once the turn ends.
+    Bronstein(Duration),
+}
+
+/// A player's time bank.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum BankBalance {
+    Remaining(Duration),
+    /// Ran out of banked time `over_by` ago.
+    Flagged {
+        over_by: Duration,
+    },
+}
+
+/// How the current turn stands against the limits.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum LimitStatus {
+    WithinLimits,
+    OverSoftLimit,
+    /// Over the hard limit, or out of banked time, for `over_by`.
+    OverHardLimit {
+        over_by: Duration,
+    },
+}
+
+impl TimeLimits {
+    /// `opt_bank_balance` is the current player's, see [Self::bank_balance].
+    pub(crate) fn status(
+        &self,
+        current_turn: Duration,
+        opt_bank_balance: Option<BankBalance>,
+    ) -> LimitStatus {
+        let over_hard_limit = self
+            .hard_limit
+            .and_then(|limit| current_turn.checked_sub(limit));
+        let over_time_bank = match opt_bank_balance {
+            Some(BankBalance::Flagged { over_by }) => Some(over_by),
+            Some(BankBalance::Remaining(_)) | None => None,
+        };
+        if let Some(over_by) = over_hard_limit.max(over_time_bank) {
+            return LimitStatus::OverHardLimit { over_by };
+        }
+
+        if self.soft_limit.is_some_and(|limit| current_turn >= limit) {
+            LimitStatus::OverSoftLimit
+        } else {
+            LimitStatus::WithinLimits
+        }
+    }
+
+    /// A player's time bank, if playing with time banks. `completed_turns` are the player's counted
+    /// turns; quick presses to skip a player are only charged, never credited.
+    pub(crate) fn bank_balance(
+        &self,
+        total_time: Duration,
+        completed_turns: impl Iterator<Item = Duration>,
+        current_turn: Duration,
+    ) -> Option<BankBalance> {
+        let credited_bank = self.time_bank? + self.clock_mode.credit(completed_turns, current_turn);
+        Some(match credited_bank.checked_sub(total_time) {
+            Some(remaining) => BankBalance::Remaining(remaining),
+            None => BankBalance::Flagged {
+                over_by: total_time - credited_bank,
+            },
+        })
+    }
+
+    pub(crate) fn to_json(self) -> TimeLimitsJson {
+        let to_secs = |opt_duration: Option<Duration>| opt_duration.map_or(0, |d| d.as_secs());
+        let (clock_mode, clock_mode_s) = match self.clock_mode {
+            ClockMode::SuddenDeath => ("sudden_death", 0),
+            ClockMode::Increment(increment) => ("increment", increment.as_secs()),
+            ClockMode::SimpleDelay(delay) => ("simple_delay", delay.as_secs()),
+            ClockMode::Bronstein(delay) => ("bronstein", delay.as_secs()),
+        };
+        TimeLimitsJson {
+            soft_limit_s: to_secs(self.soft_limit),
+            hard_limit_s: to_secs(self.hard_limit),
+            time_bank_s: to_secs(self.time_bank),
+            clock_mode: clock_mode.to_string(),
+            clock_mode_s,
+            skip_flagged_players: self.skip_flagged_players,
+        }
+    }
+
+    pub(crate) fn from_json(json: &TimeLimitsJson) -> Result<Self, String> {
+        let from_secs = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+        let clock_mode_duration = Duration::from_secs(json.clock_mode_s);
+        let clock_mode = match json.clock_mode.as_str() {
+            // Saved before there were clock modes.
+            "sudden_death" | "" => ClockMode::SuddenDeath,
+            "increment" => ClockMode::Increment(clock_mode_duration),
+            "simple_delay" => ClockMode::SimpleDelay(clock_mode_duration),
+            "bronstein" => ClockMode::Bronstein(clock_mode_duration),
+            other => return Err(format!("unknown clock mode '{other}'")),
+        };
+        Ok(Self {
+            soft_limit: from_secs(json.soft_limit_s),
+            hard_limit: from_secs(json.hard_limit_s),
+            time_bank: from_secs(json.time_bank_s),
+            clock_mode,
+            skip_flagged_players: json.skip_flagged_players,
+        })
+    }
+}
+
+impl ClockMode {
+    /// Time given back to the bank for the given turns.
+    fn credit(
+        self,
+        completed_turns: impl Iterator<Item = Duration>,
+        current_turn: Duration,
+    ) -> Duration {
+        match self {
+            ClockMode::SuddenDeath => Duration::ZERO,
+            ClockMode::Increment(increment) => increment * completed_turns.count() as u32,
+            ClockMode::SimpleDelay(delay) => {
+                completed_turns
+                    .map(|turn| turn.min(delay))
+                    .sum::<Duration>()
+                    + current_turn.min(delay)
+            }
+            ClockMode::Bronstein(delay) => completed_turns.map(|turn| turn.min(delay)).sum(),
+        }
+    }
+
+    /// Short description for the setup screen.
+    pub(crate) fn label(self) -> String {
+        match self {
+            ClockMode::SuddenDeath => "no increment".to_string(),
+            ClockMode::Increment(increment) => format!("+{}s Fischer", increment.as_secs()),
+            ClockMode::SimpleDelay(delay) => format!("{}s delay", delay.as_secs()),
+            ClockMode::Bronstein(delay) => format!("{}s Bronstein", delay.as_secs()),
+        }
+    }
+}
+
+/// Serialized form of [TimeLimits], also embedded in saved sessions.
+/// Whole seconds, 0 for no limit.
+// `pub` since nanoserde derives don't support `pub(crate)`. The module is private anyway.
+#[derive(SerJson, DeJson, Default)]
+pub struct TimeLimitsJson {
+    soft_limit_s: u64,
+    hard_limit_s: u64,
+    time_bank_s: u64,
+    /// `sudden_death`, `increment`, `simple_delay`, or `bronstein`, with `clock_mode_s` as the
+    /// increment or delay. Defaults let limits saved before clock modes still load, as sudden
+    /// death.
+    #[nserde(default)]
+    clock_mode: String,
+    #[nserde(default)]
+    clock_mode_s: u64,
+    #[nserde(default)]
+    skip_flagged_players: bool,
+}
+
+/// Limits from the last time tracking was started, or no limits.
+pub(crate) fn load_saved() -> TimeLimits {
+    bq::storage_load(SAVED_TIME_LIMITS_KEY)
+        .and_then(|json| TimeLimitsJson::deserialize_json(&json).ok())
+        .and_then(|json| TimeLimits::from_json(&json).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn save(time_limits: TimeLimits) {
+    let json = time_limits.to_json().serialize_json();
+    if let Err(e) = bq::storage_store(SAVED_TIME_LIMITS_KEY, &json) {
+        mq::warn!("Failed to save time limits: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BankBalance, ClockMode, LimitStatus, TimeLimits, TimeLimitsJson};
+    use nanoserde::DeJson;
+    use std::time::Duration;
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn test_limit_status() {
+        let limits = TimeLimits {
+            soft_limit: Some(secs(30)),
+            hard_limit: Some(secs(60)),
+            ..Default::default()
+        };
+        let remaining = Some(BankBalance::Remaining(secs(100)));
+
+        let test_cases = [
+            // (current turn, bank balance, expected status)
+            (secs(29), remaining, LimitStatus::WithinLimits),
+            (secs(30), remaining, LimitStatus::OverSoftLimit),
+            (
+                secs(65),
+                remaining,
+                LimitStatus::OverHardLimit { over_by: secs(5) },
+            ),
+            // Out of banked time mid-turn, before the hard limit.
+            (
+                secs(20),
+                Some(BankBalance::Flagged { over_by: secs(7) }),
+                LimitStatus::OverHardLimit { over_by: secs(7) },
+            ),
+        ];
+        for (current_turn, opt_bank_balance, expected_status) in test_cases {
+            assert_eq!(
+                expected_status,
+                limits.status(current_turn, opt_bank_balance)
+            );
+        }
+        assert_eq!(
+            LimitStatus::WithinLimits,
+            TimeLimits::default().status(secs(9999), None)
+        );
+    }
+
+    #[test]
+    fn test_bank_balance() {
+        // Two 20s turns done and 8s into the third, from a 60s bank.
+        let balance = |clock_mode| {
+            let limits = TimeLimits {
+                time_bank: Some(secs(60)),
+                clock_mode,
+                ..Default::default()
+            };
+            limits.bank_balance(secs(48), [secs(20), secs(20)].into_iter(), secs(8))
+        };
+        let remaining = |seconds| Some(BankBalance::Remaining(secs(seconds)));
+
+        assert_eq!(remaining(12), balance(ClockMode::SuddenDeath));
+        assert_eq!(remaining(32), balance(ClockMode::Increment(secs(10))));
+        assert_eq!(remaining(27), balance(ClockMode::SimpleDelay(secs(5))));
+        assert_eq!(remaining(22), balance(ClockMode::Bronstein(secs(5))));
+
+        let limits = TimeLimits {
+            time_bank: Some(secs(60)),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(BankBalance::Flagged { over_by: secs(3) }),
+            limits.bank_balance(secs(63), std::iter::empty(), secs(63))
+        );
+        assert_eq!(
+            None,
+            TimeLimits::default().bank_balance(secs(63), std::iter::empty(), secs(63))
+        );
+    }
+
+    #[test]
+    fn test_load_limits_from_before_clock_modes() {
+        let json = r#"{"soft_limit_s":30,"hard_limit_s":0,"time_bank_s":600}"#;
+        let limits = TimeLimits::from_json(&TimeLimitsJson::deserialize_json(json).unwrap());
+        assert_eq!(
+            Ok(TimeLimits {
+                soft_limit: Some(secs(30)),
+                time_bank: Some(secs(600)),
+                ..Default::default()
+            }),
+            limits
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Event log of turn changes, so a mistaken turn change can be rolled back, and the turns can be
+//! replayed in order for stats and reports.
+use crate::EndedTurn;
+use std::time::Duration;
+
+/// The current player changing from one player to another.
+#[derive(Copy, Clone)]
+pub(crate) struct TurnChange {
+    pub(crate) from_index: usize,
+    pub(crate) to_index: usize,
+    /// Round of the `to` player's turn, starting at 1.
+    pub(crate) to_round: usize,
+    /// The `from` player's turn that this change ended.
+    pub(crate) ended_turn: EndedTurn,
+}
+
+/// A turn in the order it was played, see [TurnLog::turns].
+#[derive(Copy, Clone)]
+pub(crate) struct LoggedTurn {
+    pub(crate) player_index: usize,
+    pub(crate) round: usize,
+    pub(crate) duration: Duration,
+    /// Quick presses to skip a player aren't counted in stats.
+    pub(crate) counted: bool,
+}
+
+/// Oldest change first.
+pub(crate) struct TurnLog {
+    changes: Vec<TurnChange>,
+}
+
+impl TurnLog {
+    pub(crate) fn new() -> Self {
+        Self {
+            changes: Vec::new(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, change: TurnChange) {
+        self.changes.push(change);
+    }
+
+    pub(crate) fn changes(&self) -> &[TurnChange] {
+        &self.changes
+    }
+
+    /// Remove and return the most recent change.
+    pub(crate) fn pop(&mut self) -> Option<TurnChange> {
+        self.changes.pop()
+    }
+
+    /// Round of the current turn, starting at 1.
+    pub(crate) fn current_round(&self) -> usize {
+        self.changes.last().map_or(1, |change| change.to_round)
+    }
+
+    /// Every ended turn in order, followed by the current turn if any time has been spent on it.
+    pub(crate) fn turns(
+        &self,
+        current_index: usize,
+        current_turn_duration: Duration,
+    ) -> Vec<LoggedTurn> {
+        let mut round = 1;
+        let mut turns = Vec::with_capacity(self.changes.len() + 1);
+        for change in &self.changes {
+            turns.push(LoggedTurn {
+                player_index: change.from_index,
+                round,
+                duration: change.ended_turn.duration,
+                counted: change.ended_turn.counted,
+            });
+            round = change.to_round;
+        }
+        if !current_turn_duration.is_zero() {
+            turns.push(LoggedTurn {
+                player_index: current_index,
+                round,
+                duration: current_turn_duration,
+                counted: true,
+            });
+        }
+        turns
+    }
+}